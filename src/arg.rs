@@ -0,0 +1,257 @@
+use crate::PrometheusDuration;
+use std::fmt;
+use std::time::SystemTime;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+/// A point in time that is passed to the Prometheus API, e.g. the evaluation time of an
+/// instant query or the start and end of a range query. See [`ToTimestamp`] for the
+/// types that can be converted into a timestamp.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timestamp(TimeRepr);
+
+#[derive(Debug, Clone, PartialEq)]
+enum TimeRepr {
+    Seconds(f64),
+    // Strings that cannot be parsed are passed to Prometheus as they are, which
+    // reports them as invalid.
+    Raw(String),
+}
+
+impl Timestamp {
+    /// Return the timestamp as Unix timestamp in seconds or `None` if it was created
+    /// from a string that is not a valid RFC 3339 timestamp.
+    pub fn seconds(&self) -> Option<f64> {
+        match &self.0 {
+            TimeRepr::Seconds(s) => Some(*s),
+            TimeRepr::Raw(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            TimeRepr::Seconds(s) => write!(f, "{}", s),
+            TimeRepr::Raw(s) => f.write_str(s),
+        }
+    }
+}
+
+/// Conversion into a [`Timestamp`].
+///
+/// This is implemented for Unix timestamps in seconds (integers and `f64`), [`time::OffsetDateTime`],
+/// [`std::time::SystemTime`] and RFC 3339 strings like `2023-07-20T10:30:00Z`.
+///
+/// ```rust
+/// use prometheus_http_query::Client;
+/// use std::time::{Duration, SystemTime};
+///
+/// let client = Client::default();
+///
+/// let one_hour_ago = SystemTime::now() - Duration::from_secs(3600);
+/// let builder = client.query_range("up", one_hour_ago, SystemTime::now(), "30s");
+///
+/// let builder = client.query("up").at("2023-07-20T10:30:00Z");
+/// let builder = client.query("up").at(time::macros::datetime!(2023-07-20 10:30 UTC));
+/// ```
+pub trait ToTimestamp {
+    /// Convert into a [`Timestamp`].
+    fn to_timestamp(self) -> Timestamp;
+}
+
+impl ToTimestamp for Timestamp {
+    fn to_timestamp(self) -> Timestamp {
+        self
+    }
+}
+
+macro_rules! seconds_to_timestamp {
+    ($($t:ty),*) => {
+        $(
+            impl ToTimestamp for $t {
+                fn to_timestamp(self) -> Timestamp {
+                    Timestamp(TimeRepr::Seconds(self as f64))
+                }
+            }
+        )*
+    };
+}
+
+seconds_to_timestamp!(i32, u32, i64, u64);
+
+impl ToTimestamp for f64 {
+    fn to_timestamp(self) -> Timestamp {
+        Timestamp(TimeRepr::Seconds(self))
+    }
+}
+
+impl ToTimestamp for OffsetDateTime {
+    fn to_timestamp(self) -> Timestamp {
+        Timestamp(TimeRepr::Seconds(
+            self.unix_timestamp_nanos() as f64 / 1_000_000_000.0,
+        ))
+    }
+}
+
+impl ToTimestamp for SystemTime {
+    fn to_timestamp(self) -> Timestamp {
+        OffsetDateTime::from(self).to_timestamp()
+    }
+}
+
+impl ToTimestamp for &str {
+    fn to_timestamp(self) -> Timestamp {
+        match OffsetDateTime::parse(self, &Rfc3339) {
+            Ok(t) => t.to_timestamp(),
+            Err(_) => Timestamp(TimeRepr::Raw(self.to_string())),
+        }
+    }
+}
+
+impl ToTimestamp for String {
+    fn to_timestamp(self) -> Timestamp {
+        self.as_str().to_timestamp()
+    }
+}
+
+/// A duration that is passed to the Prometheus API, e.g. the resolution step of a range
+/// query or a query timeout. See [`ToQueryDuration`] for the types that can be converted
+/// into a duration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryDuration(DurationRepr);
+
+#[derive(Debug, Clone, PartialEq)]
+enum DurationRepr {
+    Seconds(f64),
+    Raw(String),
+}
+
+impl QueryDuration {
+    /// Return the duration in seconds or `None` if it was created from a string that
+    /// is neither a number of seconds nor a valid [`PrometheusDuration`].
+    pub fn seconds(&self) -> Option<f64> {
+        match &self.0 {
+            DurationRepr::Seconds(s) => Some(*s),
+            DurationRepr::Raw(s) => s.parse().ok().or_else(|| {
+                s.parse::<PrometheusDuration>()
+                    .ok()
+                    .map(|d| d.as_seconds_f64())
+            }),
+        }
+    }
+}
+
+impl fmt::Display for QueryDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            DurationRepr::Seconds(s) => write!(f, "{}", s),
+            DurationRepr::Raw(s) => f.write_str(s),
+        }
+    }
+}
+
+/// Conversion into a [`QueryDuration`].
+///
+/// This is implemented for a number of seconds (`f64`), [`time::Duration`], [`std::time::Duration`],
+/// [`PrometheusDuration`] and Prometheus duration strings like `30s` or `1h30m`, which are passed to
+/// Prometheus as they are.
+///
+/// There is deliberately no implementation for integers in order to avoid confusing seconds
+/// and milliseconds.
+///
+/// ```rust
+/// use prometheus_http_query::Client;
+/// use std::time::Duration;
+///
+/// let client = Client::default();
+///
+/// let builder = client
+///     .query_range("up", 1648373100, 1648373300, "10s")
+///     .timeout(Duration::from_secs(5));
+/// ```
+pub trait ToQueryDuration {
+    /// Convert into a [`QueryDuration`].
+    fn to_query_duration(self) -> QueryDuration;
+}
+
+impl ToQueryDuration for QueryDuration {
+    fn to_query_duration(self) -> QueryDuration {
+        self
+    }
+}
+
+impl ToQueryDuration for f64 {
+    fn to_query_duration(self) -> QueryDuration {
+        QueryDuration(DurationRepr::Seconds(self))
+    }
+}
+
+impl ToQueryDuration for time::Duration {
+    fn to_query_duration(self) -> QueryDuration {
+        QueryDuration(DurationRepr::Seconds(self.as_seconds_f64()))
+    }
+}
+
+impl ToQueryDuration for std::time::Duration {
+    fn to_query_duration(self) -> QueryDuration {
+        QueryDuration(DurationRepr::Seconds(self.as_secs_f64()))
+    }
+}
+
+impl ToQueryDuration for &str {
+    fn to_query_duration(self) -> QueryDuration {
+        QueryDuration(DurationRepr::Raw(self.to_string()))
+    }
+}
+
+impl ToQueryDuration for String {
+    fn to_query_duration(self) -> QueryDuration {
+        QueryDuration(DurationRepr::Raw(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    #[test]
+    fn test_timestamps() {
+        assert_eq!(1659182624i64.to_timestamp().to_string(), "1659182624");
+        assert_eq!(1659182624.5f64.to_timestamp().to_string(), "1659182624.5");
+        assert_eq!(
+            datetime!(2022-07-30 12:03:44.5 UTC).to_timestamp(),
+            1659182624.5f64.to_timestamp()
+        );
+        assert_eq!(
+            "2022-07-30T14:03:44+02:00".to_timestamp(),
+            1659182624u32.to_timestamp()
+        );
+        assert_eq!(SystemTime::UNIX_EPOCH.to_timestamp().seconds(), Some(0.0));
+
+        let invalid = "yesterday".to_timestamp();
+        assert_eq!(invalid.seconds(), None);
+        assert_eq!(invalid.to_string(), "yesterday");
+    }
+
+    #[test]
+    fn test_query_durations() {
+        assert_eq!(15f64.to_query_duration().to_string(), "15");
+        assert_eq!(
+            std::time::Duration::from_millis(1500)
+                .to_query_duration()
+                .to_string(),
+            "1.5"
+        );
+        assert_eq!(
+            time::Duration::minutes(1).to_query_duration().seconds(),
+            Some(60.0)
+        );
+        assert_eq!("30s".to_query_duration().to_string(), "30s");
+        assert_eq!("30s".to_query_duration().seconds(), Some(30.0));
+        assert_eq!("1m30s".to_query_duration().seconds(), Some(90.0));
+        assert_eq!("soon".to_query_duration().seconds(), None);
+        assert_eq!("30".to_query_duration().seconds(), Some(30.0));
+    }
+}
@@ -0,0 +1,712 @@
+//! Types that represent the abstract syntax tree (AST) of a PromQL expression
+//! as returned by [`Client::parse_query`](crate::Client::parse_query).
+use crate::response::de;
+use serde::{Deserialize, Deserializer};
+use std::fmt;
+use time::Duration;
+
+// The API returns all durations as integer milliseconds.
+fn deserialize_milliseconds<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    i64::deserialize(deserializer).map(Duration::milliseconds)
+}
+
+/// A single node of the PromQL syntax tree.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(tag = "type")]
+pub enum Node {
+    #[serde(alias = "aggregation")]
+    Aggregation(Aggregation),
+    #[serde(alias = "binaryExpr")]
+    BinaryExpr(BinaryExpr),
+    #[serde(alias = "call")]
+    Call(Call),
+    #[serde(alias = "matrixSelector")]
+    MatrixSelector(MatrixSelector),
+    #[serde(alias = "subquery")]
+    Subquery(Subquery),
+    #[serde(alias = "numberLiteral")]
+    NumberLiteral(NumberLiteral),
+    #[serde(alias = "parenExpr")]
+    ParenExpr(ParenExpr),
+    #[serde(alias = "stringLiteral")]
+    StringLiteral(StringLiteral),
+    #[serde(alias = "unaryExpr")]
+    UnaryExpr(UnaryExpr),
+    #[serde(alias = "vectorSelector")]
+    VectorSelector(VectorSelector),
+}
+
+impl Node {
+    pub fn as_aggregation(&self) -> Option<&Aggregation> {
+        match self {
+            Self::Aggregation(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    pub fn as_binary_expr(&self) -> Option<&BinaryExpr> {
+        match self {
+            Self::BinaryExpr(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    pub fn as_call(&self) -> Option<&Call> {
+        match self {
+            Self::Call(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    pub fn as_matrix_selector(&self) -> Option<&MatrixSelector> {
+        match self {
+            Self::MatrixSelector(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    pub fn as_subquery(&self) -> Option<&Subquery> {
+        match self {
+            Self::Subquery(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    pub fn as_number_literal(&self) -> Option<&NumberLiteral> {
+        match self {
+            Self::NumberLiteral(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    pub fn as_paren_expr(&self) -> Option<&ParenExpr> {
+        match self {
+            Self::ParenExpr(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    pub fn as_string_literal(&self) -> Option<&StringLiteral> {
+        match self {
+            Self::StringLiteral(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    pub fn as_unary_expr(&self) -> Option<&UnaryExpr> {
+        match self {
+            Self::UnaryExpr(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    pub fn as_vector_selector(&self) -> Option<&VectorSelector> {
+        match self {
+            Self::VectorSelector(n) => Some(n),
+            _ => None,
+        }
+    }
+}
+
+/// An aggregation like `sum by (job) (up)` or `topk(5, up)`.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct Aggregation {
+    pub(crate) op: String,
+    pub(crate) expr: Box<Node>,
+    pub(crate) param: Option<Box<Node>>,
+    #[serde(default)]
+    pub(crate) grouping: Vec<String>,
+    pub(crate) without: bool,
+}
+
+impl Aggregation {
+    /// Get the aggregation operator, e.g. `sum` or `topk`.
+    pub fn op(&self) -> &str {
+        &self.op
+    }
+
+    /// Get the expression that is aggregated.
+    pub fn expr(&self) -> &Node {
+        &self.expr
+    }
+
+    /// Get the parameter of the aggregation, e.g. `5` in `topk(5, up)`.
+    pub fn param(&self) -> Option<&Node> {
+        self.param.as_deref()
+    }
+
+    /// Get the list of labels in the `by` or `without` clause.
+    pub fn grouping(&self) -> &[String] {
+        &self.grouping
+    }
+
+    /// Check if the grouping labels are excluded (`without`) instead of preserved (`by`).
+    pub fn without(&self) -> bool {
+        self.without
+    }
+}
+
+/// A binary expression like `a + b` or `a > bool b`.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct BinaryExpr {
+    pub(crate) op: String,
+    pub(crate) lhs: Box<Node>,
+    pub(crate) rhs: Box<Node>,
+    pub(crate) matching: Option<VectorMatching>,
+    pub(crate) bool: bool,
+}
+
+impl BinaryExpr {
+    /// Get the binary operator, e.g. `+` or `and`.
+    pub fn op(&self) -> &str {
+        &self.op
+    }
+
+    /// Get the left-hand side operand.
+    pub fn lhs(&self) -> &Node {
+        &self.lhs
+    }
+
+    /// Get the right-hand side operand.
+    pub fn rhs(&self) -> &Node {
+        &self.rhs
+    }
+
+    /// Get the vector matching behaviour if both operands are vectors.
+    pub fn matching(&self) -> Option<&VectorMatching> {
+        self.matching.as_ref()
+    }
+
+    /// Check if the comparison operator is used with the `bool` modifier.
+    pub fn return_bool(&self) -> bool {
+        self.bool
+    }
+}
+
+/// Describes how the samples of two vectors are matched in a binary expression.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct VectorMatching {
+    pub(crate) card: MatchingCardinality,
+    #[serde(default)]
+    pub(crate) labels: Vec<String>,
+    pub(crate) on: bool,
+    #[serde(default)]
+    pub(crate) include: Vec<String>,
+}
+
+impl VectorMatching {
+    /// Get the cardinality of the matching.
+    pub fn card(&self) -> MatchingCardinality {
+        self.card
+    }
+
+    /// Get the labels listed in the `on` or `ignoring` clause.
+    pub fn labels(&self) -> &[String] {
+        &self.labels
+    }
+
+    /// Check if the labels are used to match (`on`) instead of being ignored (`ignoring`).
+    pub fn on(&self) -> bool {
+        self.on
+    }
+
+    /// Get the labels listed in the `group_left` or `group_right` clause.
+    pub fn include(&self) -> &[String] {
+        &self.include
+    }
+}
+
+/// The cardinality of a vector matching.
+#[derive(Debug, Copy, Clone, Deserialize, Eq, PartialEq)]
+pub enum MatchingCardinality {
+    #[serde(alias = "one-to-one")]
+    OneToOne,
+    #[serde(alias = "many-to-one")]
+    ManyToOne,
+    #[serde(alias = "one-to-many")]
+    OneToMany,
+    #[serde(alias = "many-to-many")]
+    ManyToMany,
+}
+
+impl fmt::Display for MatchingCardinality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OneToOne => f.write_str("one-to-one"),
+            Self::ManyToOne => f.write_str("many-to-one"),
+            Self::OneToMany => f.write_str("one-to-many"),
+            Self::ManyToMany => f.write_str("many-to-many"),
+        }
+    }
+}
+
+/// A function call like `rate(http_requests_total[5m])`.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct Call {
+    pub(crate) func: Function,
+    #[serde(default)]
+    pub(crate) args: Vec<Node>,
+}
+
+impl Call {
+    /// Get the signature of the function that is called.
+    pub fn func(&self) -> &Function {
+        &self.func
+    }
+
+    /// Get the arguments that the function is called with.
+    pub fn args(&self) -> &[Node] {
+        &self.args
+    }
+}
+
+/// The signature of a PromQL function.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct Function {
+    pub(crate) name: String,
+    #[serde(alias = "argTypes", default)]
+    pub(crate) arg_types: Vec<ValueType>,
+    pub(crate) variadic: i64,
+    #[serde(alias = "returnType")]
+    pub(crate) return_type: ValueType,
+}
+
+impl Function {
+    /// Get the name of the function.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the types of the arguments of the function.
+    pub fn arg_types(&self) -> &[ValueType] {
+        &self.arg_types
+    }
+
+    /// Get the number of optional arguments of the function. A negative
+    /// number indicates an unlimited number of optional arguments.
+    pub fn variadic(&self) -> i64 {
+        self.variadic
+    }
+
+    /// Get the type of the value that the function returns.
+    pub fn return_type(&self) -> ValueType {
+        self.return_type
+    }
+}
+
+/// The type of value that a PromQL expression evaluates to.
+#[derive(Debug, Copy, Clone, Deserialize, Eq, PartialEq)]
+pub enum ValueType {
+    #[serde(alias = "none")]
+    None,
+    #[serde(alias = "vector")]
+    Vector,
+    #[serde(alias = "scalar")]
+    Scalar,
+    #[serde(alias = "matrix")]
+    Matrix,
+    #[serde(alias = "string")]
+    String,
+}
+
+impl fmt::Display for ValueType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::None => f.write_str("none"),
+            Self::Vector => f.write_str("vector"),
+            Self::Scalar => f.write_str("scalar"),
+            Self::Matrix => f.write_str("matrix"),
+            Self::String => f.write_str("string"),
+        }
+    }
+}
+
+/// A vector selector like `http_requests_total{job="api"} offset 5m`.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct VectorSelector {
+    pub(crate) name: String,
+    #[serde(deserialize_with = "deserialize_milliseconds")]
+    pub(crate) offset: Duration,
+    #[serde(default)]
+    pub(crate) matchers: Vec<LabelMatcher>,
+    pub(crate) timestamp: Option<i64>,
+    #[serde(alias = "startOrEnd")]
+    pub(crate) start_or_end: Option<StartOrEnd>,
+}
+
+impl VectorSelector {
+    /// Get the metric name of the selector. This is empty if the metric name
+    /// was not set explicitly outside of the label matchers.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the offset of the selector.
+    pub fn offset(&self) -> &Duration {
+        &self.offset
+    }
+
+    /// Get the label matchers of the selector (including the metric name matcher).
+    pub fn matchers(&self) -> &[LabelMatcher] {
+        &self.matchers
+    }
+
+    /// Get the evaluation timestamp (Unix timestamp in milliseconds) set by the `@` modifier.
+    pub fn timestamp(&self) -> Option<i64> {
+        self.timestamp
+    }
+
+    /// Get the evaluation time set by the `@ start()` or `@ end()` modifier.
+    pub fn start_or_end(&self) -> Option<StartOrEnd> {
+        self.start_or_end
+    }
+}
+
+/// A range vector selector like `http_requests_total{job="api"}[5m]`.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct MatrixSelector {
+    pub(crate) name: String,
+    #[serde(deserialize_with = "deserialize_milliseconds")]
+    pub(crate) range: Duration,
+    #[serde(deserialize_with = "deserialize_milliseconds")]
+    pub(crate) offset: Duration,
+    #[serde(default)]
+    pub(crate) matchers: Vec<LabelMatcher>,
+    pub(crate) timestamp: Option<i64>,
+    #[serde(alias = "startOrEnd")]
+    pub(crate) start_or_end: Option<StartOrEnd>,
+}
+
+impl MatrixSelector {
+    /// Get the metric name of the selector. This is empty if the metric name
+    /// was not set explicitly outside of the label matchers.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the range of the selector.
+    pub fn range(&self) -> &Duration {
+        &self.range
+    }
+
+    /// Get the offset of the selector.
+    pub fn offset(&self) -> &Duration {
+        &self.offset
+    }
+
+    /// Get the label matchers of the selector (including the metric name matcher).
+    pub fn matchers(&self) -> &[LabelMatcher] {
+        &self.matchers
+    }
+
+    /// Get the evaluation timestamp (Unix timestamp in milliseconds) set by the `@` modifier.
+    pub fn timestamp(&self) -> Option<i64> {
+        self.timestamp
+    }
+
+    /// Get the evaluation time set by the `@ start()` or `@ end()` modifier.
+    pub fn start_or_end(&self) -> Option<StartOrEnd> {
+        self.start_or_end
+    }
+}
+
+/// A subquery like `rate(http_requests_total[5m])[30m:1m]`.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct Subquery {
+    pub(crate) expr: Box<Node>,
+    #[serde(deserialize_with = "deserialize_milliseconds")]
+    pub(crate) range: Duration,
+    #[serde(deserialize_with = "deserialize_milliseconds")]
+    pub(crate) offset: Duration,
+    #[serde(deserialize_with = "deserialize_milliseconds")]
+    pub(crate) step: Duration,
+    pub(crate) timestamp: Option<i64>,
+    #[serde(alias = "startOrEnd")]
+    pub(crate) start_or_end: Option<StartOrEnd>,
+}
+
+impl Subquery {
+    /// Get the inner expression of the subquery.
+    pub fn expr(&self) -> &Node {
+        &self.expr
+    }
+
+    /// Get the range of the subquery.
+    pub fn range(&self) -> &Duration {
+        &self.range
+    }
+
+    /// Get the offset of the subquery.
+    pub fn offset(&self) -> &Duration {
+        &self.offset
+    }
+
+    /// Get the resolution step of the subquery. This is zero if the step
+    /// was omitted and the global evaluation interval is used instead.
+    pub fn step(&self) -> &Duration {
+        &self.step
+    }
+
+    /// Get the evaluation timestamp (Unix timestamp in milliseconds) set by the `@` modifier.
+    pub fn timestamp(&self) -> Option<i64> {
+        self.timestamp
+    }
+
+    /// Get the evaluation time set by the `@ start()` or `@ end()` modifier.
+    pub fn start_or_end(&self) -> Option<StartOrEnd> {
+        self.start_or_end
+    }
+}
+
+/// A single label matcher of a selector.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct LabelMatcher {
+    pub(crate) name: String,
+    pub(crate) value: String,
+    #[serde(alias = "type")]
+    pub(crate) match_type: MatchType,
+}
+
+impl LabelMatcher {
+    /// Get the label name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the value that the label is matched against.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Get the type of the matcher.
+    pub fn match_type(&self) -> MatchType {
+        self.match_type
+    }
+}
+
+/// The type of a label matcher.
+#[derive(Debug, Copy, Clone, Deserialize, Eq, PartialEq)]
+pub enum MatchType {
+    #[serde(alias = "=")]
+    Equal,
+    #[serde(alias = "!=")]
+    NotEqual,
+    #[serde(alias = "=~")]
+    RegexEqual,
+    #[serde(alias = "!~")]
+    RegexNotEqual,
+}
+
+impl fmt::Display for MatchType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Equal => f.write_str("="),
+            Self::NotEqual => f.write_str("!="),
+            Self::RegexEqual => f.write_str("=~"),
+            Self::RegexNotEqual => f.write_str("!~"),
+        }
+    }
+}
+
+/// The evaluation time set by the `@ start()` or `@ end()` modifier.
+#[derive(Debug, Copy, Clone, Deserialize, Eq, PartialEq)]
+pub enum StartOrEnd {
+    #[serde(alias = "start")]
+    Start,
+    #[serde(alias = "end")]
+    End,
+}
+
+/// A number literal like `5` or `Inf`.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+pub struct NumberLiteral {
+    #[serde(deserialize_with = "de::deserialize_f64")]
+    pub(crate) val: f64,
+}
+
+impl NumberLiteral {
+    /// Get the value of the number literal.
+    pub fn val(&self) -> f64 {
+        self.val
+    }
+}
+
+/// An expression wrapped in parentheses.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct ParenExpr {
+    pub(crate) expr: Box<Node>,
+}
+
+impl ParenExpr {
+    /// Get the expression inside the parentheses.
+    pub fn expr(&self) -> &Node {
+        &self.expr
+    }
+}
+
+/// A string literal like `"foo"`.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct StringLiteral {
+    pub(crate) val: String,
+}
+
+impl StringLiteral {
+    /// Get the value of the string literal.
+    pub fn val(&self) -> &str {
+        &self.val
+    }
+}
+
+/// A unary expression like `-up`.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct UnaryExpr {
+    pub(crate) op: String,
+    pub(crate) expr: Box<Node>,
+}
+
+impl UnaryExpr {
+    /// Get the unary operator, i.e. `-` or `+`.
+    pub fn op(&self) -> &str {
+        &self.op
+    }
+
+    /// Get the expression that the operator is applied to.
+    pub fn expr(&self) -> &Node {
+        &self.expr
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_query_deserialization() -> Result<(), anyhow::Error> {
+        // The AST of: sum by (job) (rate(http_requests_total{code!~"5.."}[5m] offset 1m)) / on (job) group_left (team) -team_info @ end()
+        let data = r#"
+{
+  "type": "binaryExpr",
+  "op": "/",
+  "bool": false,
+  "lhs": {
+    "type": "aggregation",
+    "op": "sum",
+    "grouping": ["job"],
+    "without": false,
+    "param": null,
+    "expr": {
+      "type": "call",
+      "func": {
+        "name": "rate",
+        "argTypes": ["matrix"],
+        "variadic": 0,
+        "returnType": "vector"
+      },
+      "args": [
+        {
+          "type": "matrixSelector",
+          "name": "http_requests_total",
+          "range": 300000,
+          "offset": 60000,
+          "matchers": [
+            { "name": "code", "value": "5..", "type": "!~" },
+            { "name": "__name__", "value": "http_requests_total", "type": "=" }
+          ],
+          "timestamp": null,
+          "startOrEnd": null
+        }
+      ]
+    }
+  },
+  "matching": {
+    "card": "many-to-one",
+    "labels": ["job"],
+    "on": true,
+    "include": ["team"]
+  },
+  "rhs": {
+    "type": "unaryExpr",
+    "op": "-",
+    "expr": {
+      "type": "vectorSelector",
+      "name": "team_info",
+      "offset": 0,
+      "matchers": [
+        { "name": "__name__", "value": "team_info", "type": "=" }
+      ],
+      "timestamp": null,
+      "startOrEnd": "end"
+    }
+  }
+}
+"#;
+        let node = serde_json::from_str::<Node>(data)?;
+        let binary = node.as_binary_expr().unwrap();
+        assert!(binary.op() == "/");
+        assert!(!binary.return_bool());
+        let matching = binary.matching().unwrap();
+        assert!(matching.card() == MatchingCardinality::ManyToOne);
+        assert!(matching.on());
+        assert!(matching.labels() == ["job"]);
+        assert!(matching.include() == ["team"]);
+        let aggregation = binary.lhs().as_aggregation().unwrap();
+        assert!(aggregation.op() == "sum");
+        assert!(aggregation.grouping() == ["job"]);
+        assert!(!aggregation.without());
+        assert!(aggregation.param().is_none());
+        let call = aggregation.expr().as_call().unwrap();
+        assert!(call.func().name() == "rate");
+        assert!(call.func().arg_types() == [ValueType::Matrix]);
+        assert!(call.func().return_type() == ValueType::Vector);
+        let selector = call.args()[0].as_matrix_selector().unwrap();
+        assert!(selector.name() == "http_requests_total");
+        assert!(selector.range() == &Duration::minutes(5));
+        assert!(selector.offset() == &Duration::minutes(1));
+        assert!(selector.matchers()[0].match_type() == MatchType::RegexNotEqual);
+        assert!(selector.timestamp().is_none());
+        let unary = binary.rhs().as_unary_expr().unwrap();
+        assert!(unary.op() == "-");
+        let selector = unary.expr().as_vector_selector().unwrap();
+        assert!(selector.start_or_end() == Some(StartOrEnd::End));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_query_literals_deserialization() -> Result<(), anyhow::Error> {
+        let data = r#"
+{
+  "type": "subquery",
+  "expr": {
+    "type": "parenExpr",
+    "expr": { "type": "numberLiteral", "val": "+Inf" }
+  },
+  "range": 1800000,
+  "offset": 0,
+  "step": 60000,
+  "timestamp": 1700000000000,
+  "startOrEnd": null
+}
+"#;
+        let node = serde_json::from_str::<Node>(data)?;
+        let subquery = node.as_subquery().unwrap();
+        assert!(subquery.range() == &Duration::minutes(30));
+        assert!(subquery.step() == &Duration::minutes(1));
+        assert!(subquery.timestamp() == Some(1700000000000));
+        let number = subquery
+            .expr()
+            .as_paren_expr()
+            .unwrap()
+            .expr()
+            .as_number_literal()
+            .unwrap();
+        assert!(number.val() == f64::INFINITY);
+
+        let data = r#"{ "type": "stringLiteral", "val": "foo" }"#;
+        let node = serde_json::from_str::<Node>(data)?;
+        assert!(node.as_string_literal().is_some_and(|s| s.val() == "foo"));
+        Ok(())
+    }
+}
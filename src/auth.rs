@@ -0,0 +1,342 @@
+use crate::error::{AuthError, Error, OAuth2Error, ParseUrlError};
+#[cfg(feature = "aws-sigv4")]
+use crate::sigv4::SigV4;
+use reqwest::header::HeaderValue;
+use serde::Deserialize;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use url::Url;
+
+/// Credentials that are attached to every request sent by a [`Client`](crate::Client),
+/// see [`ClientBuilder::auth`](crate::ClientBuilder::auth).
+#[derive(Clone)]
+#[non_exhaustive]
+pub enum Auth {
+    /// HTTP basic authentication.
+    Basic {
+        username: String,
+        password: Option<String>,
+    },
+    /// A static bearer token.
+    Bearer(String),
+    /// A bearer token that is read from a file. The file is re-read for every request,
+    /// so that tokens that are rotated on disk are picked up without recreating the [`Client`](crate::Client).
+    BearerFile(PathBuf),
+    /// A bearer token that is obtained using the OAuth2 client credentials flow.
+    OAuth2(OAuth2),
+    /// AWS Signature Version 4 request signing (requires the `aws-sigv4` feature).
+    #[cfg(feature = "aws-sigv4")]
+    SigV4(SigV4),
+}
+
+impl fmt::Debug for Auth {
+    // Credentials are redacted in order to not leak them into logs.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Basic { username, .. } => f
+                .debug_struct("Basic")
+                .field("username", username)
+                .field("password", &"<redacted>")
+                .finish(),
+            Self::Bearer(_) => f.debug_tuple("Bearer").field(&"<redacted>").finish(),
+            Self::BearerFile(path) => f.debug_tuple("BearerFile").field(path).finish(),
+            Self::OAuth2(oauth2) => f.debug_tuple("OAuth2").field(oauth2).finish(),
+            #[cfg(feature = "aws-sigv4")]
+            Self::SigV4(sigv4) => f.debug_tuple("SigV4").field(sigv4).finish(),
+        }
+    }
+}
+
+impl Auth {
+    // Attach the credentials to the request, obtaining them first if necessary.
+    pub(crate) async fn apply(
+        &self,
+        request: reqwest::RequestBuilder,
+        client: &reqwest::Client,
+    ) -> Result<reqwest::RequestBuilder, Error> {
+        match self {
+            Self::Basic { username, password } => {
+                Ok(request.basic_auth(username, password.as_ref()))
+            }
+            Self::Bearer(token) => Ok(request.bearer_auth(token)),
+            Self::BearerFile(path) => {
+                let token = tokio::fs::read_to_string(path).await.map_err(|source| {
+                    Error::Auth(AuthError {
+                        message: "failed to read bearer token from file",
+                        source: Some(source),
+                    })
+                })?;
+                Ok(request.bearer_auth(token.trim()))
+            }
+            Self::OAuth2(oauth2) => {
+                let token = oauth2.token(client).await?;
+                Ok(request.bearer_auth(token))
+            }
+            // Signing requires the final request, see `Auth::sign`.
+            #[cfg(feature = "aws-sigv4")]
+            Self::SigV4(_) => Ok(request),
+        }
+    }
+
+    // Sign the final request if required by this authentication method.
+    pub(crate) fn sign(&self, request: &mut reqwest::Request) {
+        #[cfg(feature = "aws-sigv4")]
+        if let Self::SigV4(sigv4) = self {
+            sigv4.sign(request, time::OffsetDateTime::now_utc());
+        }
+        #[cfg(not(feature = "aws-sigv4"))]
+        let _ = request;
+    }
+
+    // Discard cached credentials after the server rejected the given `Authorization` header.
+    // Returns `true` if the request should be retried with fresh credentials.
+    pub(crate) async fn invalidate(&self, rejected: Option<&HeaderValue>) -> bool {
+        match self {
+            Self::OAuth2(oauth2) => {
+                oauth2.invalidate(rejected).await;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Configuration of the OAuth2 client credentials flow, see [`Auth::OAuth2`].
+///
+/// The access token is cached and shared between all clones of the [`Client`](crate::Client).
+/// It is refreshed shortly before it expires or when Prometheus responds with HTTP 401. Concurrent
+/// requests wait for a single token request instead of each requesting a token of their own.
+///
+/// ```rust
+/// use prometheus_http_query::{Auth, Client, OAuth2};
+///
+/// fn main() -> Result<(), anyhow::Error> {
+///     let oauth2 = OAuth2::new("client-id", "client-secret", "https://sso.example.com/token")?
+///         .scopes(["metrics:read"])
+///         .param("audience", "prometheus");
+///
+///     let client = Client::builder()
+///         .url("https://prometheus.example.com")
+///         .auth(Auth::OAuth2(oauth2))
+///         .build()?;
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone)]
+pub struct OAuth2 {
+    client_id: String,
+    client_secret: String,
+    token_url: Url,
+    scopes: Vec<String>,
+    params: Vec<(String, String)>,
+    cache: Arc<Mutex<Option<Token>>>,
+}
+
+#[derive(Clone)]
+struct Token {
+    access_token: String,
+    expires_at: Option<Instant>,
+}
+
+impl Token {
+    fn is_fresh(&self) -> bool {
+        match self.expires_at {
+            Some(expires_at) => Instant::now() + EXPIRY_DELTA < expires_at,
+            None => true,
+        }
+    }
+}
+
+// Tokens are refreshed this long before they actually expire in order to account
+// for clock skew and request latency.
+const EXPIRY_DELTA: Duration = Duration::from_secs(10);
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct TokenErrorResponse {
+    error: String,
+    error_description: Option<String>,
+}
+
+impl fmt::Debug for OAuth2 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OAuth2")
+            .field("client_id", &self.client_id)
+            .field("client_secret", &"<redacted>")
+            .field("token_url", &self.token_url.as_str())
+            .field("scopes", &self.scopes)
+            .field("params", &self.params)
+            .finish()
+    }
+}
+
+impl OAuth2 {
+    /// Create a new OAuth2 configuration. Fails if the token URL is invalid.
+    pub fn new(
+        client_id: impl std::fmt::Display,
+        client_secret: impl std::fmt::Display,
+        token_url: &str,
+    ) -> Result<Self, Error> {
+        let token_url = Url::parse(token_url).map_err(|source| {
+            Error::ParseUrl(ParseUrlError {
+                message: "failed to parse OAuth2 token URL",
+                source,
+            })
+        })?;
+
+        Ok(OAuth2 {
+            client_id: client_id.to_string(),
+            client_secret: client_secret.to_string(),
+            token_url,
+            scopes: vec![],
+            params: vec![],
+            cache: Default::default(),
+        })
+    }
+
+    /// Set the scopes to request.
+    /// Calling this repeatedly will replace the current scopes.
+    pub fn scopes<T>(mut self, scopes: T) -> Self
+    where
+        T: IntoIterator,
+        T::Item: std::fmt::Display,
+    {
+        self.scopes = scopes.into_iter().map(|s| s.to_string()).collect();
+        self
+    }
+
+    /// Include an additional parameter in the token request, e.g. `audience`.
+    pub fn param(mut self, name: impl std::fmt::Display, value: impl std::fmt::Display) -> Self {
+        self.params.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    // Return the cached access token or fetch a new one if there is none or it has expired.
+    // The cache is locked while fetching, so that concurrent requests wait for a single
+    // token request instead of sending their own.
+    async fn token(&self, client: &reqwest::Client) -> Result<String, Error> {
+        let mut cache = self.cache.lock().await;
+
+        if let Some(token) = cache.as_ref().filter(|t| t.is_fresh()) {
+            return Ok(token.access_token.clone());
+        }
+
+        let token = self.fetch(client).await?;
+        let access_token = token.access_token.clone();
+        *cache = Some(token);
+        Ok(access_token)
+    }
+
+    // Discard the cached token unless another request already replaced the rejected one.
+    async fn invalidate(&self, rejected: Option<&HeaderValue>) {
+        let mut cache = self.cache.lock().await;
+
+        let cached = cache.as_ref().map(|t| format!("Bearer {}", t.access_token));
+
+        if cached.as_deref().map(str::as_bytes) == rejected.map(HeaderValue::as_bytes) {
+            *cache = None;
+        }
+    }
+
+    async fn fetch(&self, client: &reqwest::Client) -> Result<Token, Error> {
+        let mut form = vec![("grant_type", String::from("client_credentials"))];
+
+        if !self.scopes.is_empty() {
+            form.push(("scope", self.scopes.join(" ")));
+        }
+
+        form.extend(self.params.iter().map(|(k, v)| (k.as_str(), v.clone())));
+
+        let requested_at = Instant::now();
+
+        let response = client
+            .post(self.token_url.clone())
+            .basic_auth(&self.client_id, Some(&self.client_secret))
+            .form(&form)
+            .send()
+            .await
+            .map_err(|source| {
+                Error::OAuth2(OAuth2Error {
+                    message: "failed to send request to OAuth2 token endpoint",
+                    source: Some(source.into()),
+                    details: None,
+                })
+            })?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let details = match response.json::<TokenErrorResponse>().await {
+                Ok(e) => match e.error_description {
+                    Some(description) => format!("{}: {}", e.error, description),
+                    None => e.error,
+                },
+                Err(_) => status.to_string(),
+            };
+            return Err(Error::OAuth2(OAuth2Error {
+                message: "OAuth2 token endpoint returned an error",
+                source: None,
+                details: Some(details),
+            }));
+        }
+
+        let token = response.json::<TokenResponse>().await.map_err(|source| {
+            Error::OAuth2(OAuth2Error {
+                message: "failed to parse OAuth2 token response",
+                source: Some(source.into()),
+                details: None,
+            })
+        })?;
+
+        Ok(Token {
+            access_token: token.access_token,
+            expires_at: token
+                .expires_in
+                // Tokens that expire too far in the future to be represented never expire.
+                .and_then(|secs| requested_at.checked_add(Duration::from_secs(secs))),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_oauth2_token_cache() -> Result<(), anyhow::Error> {
+        let token = |access_token: &str, expires_in: Option<u64>| Token {
+            access_token: access_token.to_string(),
+            expires_at: expires_in.map(|secs| Instant::now() + Duration::from_secs(secs)),
+        };
+        assert!(token("valid", Some(3600)).is_fresh());
+        assert!(!token("expiring", Some(5)).is_fresh());
+        assert!(token("forever", None).is_fresh());
+
+        // Clones share the cache.
+        let oauth2 = OAuth2::new("id", "secret", "https://sso.example.com/token")?;
+        *oauth2.cache.lock().await = Some(token("stale", Some(3600)));
+        let auth = Auth::OAuth2(oauth2.clone());
+
+        // The cached token has been refreshed since the rejected request was sent.
+        let other = HeaderValue::from_static("Bearer other");
+        assert!(auth.invalidate(Some(&other)).await);
+        assert!(oauth2.cache.lock().await.is_some());
+
+        let stale = HeaderValue::from_static("Bearer stale");
+        assert!(auth.invalidate(Some(&stale)).await);
+        assert!(oauth2.cache.lock().await.is_none());
+
+        assert!(!Auth::Bearer(String::from("token")).invalidate(None).await);
+        assert!(!format!("{:?}", auth).contains("secret\""));
+
+        Ok(())
+    }
+}
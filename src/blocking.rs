@@ -0,0 +1,833 @@
+//! A synchronous client for the Prometheus API (requires the `blocking` feature).
+//!
+//! The [`blocking::Client`](Client) mirrors the asynchronous [`crate::Client`] and its
+//! query builders, but blocks the current thread until the response has been received. Requests
+//! are driven by a single-threaded runtime that is owned by the client and shared among its clones,
+//! so no runtime has to be set up by the caller.
+//!
+//! Like `reqwest::blocking`, the blocking client must not be used from within an asynchronous
+//! runtime as blocking inside it panics.
+//!
+//! ```rust
+//! use prometheus_http_query::blocking::Client;
+//!
+//! fn main() -> Result<(), anyhow::Error> {
+//!     let client = Client::default();
+//!
+//!     let response = client.query("up").get()?;
+//!
+//!     assert!(response.data().as_vector().is_some());
+//!     Ok(())
+//! }
+//! ```
+use crate::arg::{ToQueryDuration, ToTimestamp};
+use crate::ast::Node;
+use crate::error::{Error, RuntimeError};
+use crate::response::*;
+use crate::selector::Selector;
+use crate::util::{RuleKind, TargetState};
+use crate::{ClientBuilder, SeriesStream};
+use futures_util::StreamExt;
+use reqwest::header::{HeaderValue, IntoHeaderName};
+use serde::de::DeserializeOwned;
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+use url::Url;
+
+/// A synchronous client that sends requests to the Prometheus API, see [`crate::Client`].
+#[derive(Clone)]
+pub struct Client {
+    client: crate::Client,
+    runtime: Arc<Runtime>,
+}
+
+impl Client {
+    /// Create a blocking client from an asynchronous [`crate::Client`], keeping all of its
+    /// settings. Fails if the runtime that drives the requests cannot be created.
+    ///
+    /// ```rust
+    /// use prometheus_http_query::{blocking, Client};
+    ///
+    /// fn main() -> Result<(), anyhow::Error> {
+    ///     let client = Client::builder()
+    ///         .url("https://prometheus.example.com")
+    ///         .bearer_auth("token")
+    ///         .build()?;
+    ///
+    ///     let client = blocking::Client::new(client)?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn new(client: crate::Client) -> Result<Self, Error> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|source| {
+                Error::Runtime(RuntimeError {
+                    message: "failed to create runtime for the blocking client",
+                    source,
+                })
+            })?;
+
+        Ok(Client {
+            client,
+            runtime: Arc::new(runtime),
+        })
+    }
+
+    /// Create a [`ClientBuilder`] to configure settings that apply to all requests.
+    /// Use [`ClientBuilder::build_blocking`] to create a blocking client.
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::default()
+    }
+
+    /// Create a Client from a custom [`reqwest::Client`] and URL, see [`crate::Client::from`].
+    pub fn from(client: reqwest::Client, url: &str) -> Result<Self, Error> {
+        ClientBuilder::default()
+            .client(client)
+            .url(url)
+            .build_blocking()
+    }
+
+    /// Return a reference to the wrapped asynchronous [`crate::Client`].
+    pub fn inner(&self) -> &crate::Client {
+        &self.client
+    }
+
+    /// Return a reference to the base URL that is used in requests to
+    /// the Prometheus API.
+    pub fn base_url(&self) -> &Url {
+        self.client.base_url()
+    }
+
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+
+    fn wrap<T>(&self, builder: T) -> Builder<T> {
+        Builder {
+            inner: builder,
+            runtime: Arc::clone(&self.runtime),
+        }
+    }
+
+    /// Create an [`InstantQueryBuilder`] from a PromQL query, see [`crate::Client::query`].
+    pub fn query(&self, query: impl std::fmt::Display) -> InstantQueryBuilder {
+        self.wrap(self.client.query(query))
+    }
+
+    /// Create a [`RangeQueryBuilder`] from a PromQL query, see [`crate::Client::query_range`].
+    pub fn query_range(
+        &self,
+        query: impl std::fmt::Display,
+        start: impl ToTimestamp,
+        end: impl ToTimestamp,
+        step: impl ToQueryDuration,
+    ) -> RangeQueryBuilder {
+        self.wrap(self.client.query_range(query, start, end, step))
+    }
+
+    /// Create an [`ExemplarsQueryBuilder`] from a PromQL query, see [`crate::Client::query_exemplars`].
+    pub fn query_exemplars(
+        &self,
+        query: impl std::fmt::Display,
+        start: impl ToTimestamp,
+        end: impl ToTimestamp,
+    ) -> ExemplarsQueryBuilder {
+        self.wrap(self.client.query_exemplars(query, start, end))
+    }
+
+    /// Format a PromQL query, see [`crate::Client::format_query`].
+    pub fn format_query(&self, query: impl std::fmt::Display) -> Result<String, Error> {
+        self.block_on(self.client.format_query(query))
+    }
+
+    /// Parse a PromQL query into its syntax tree, see [`crate::Client::parse_query`].
+    pub fn parse_query(&self, query: impl std::fmt::Display) -> Result<Node, Error> {
+        self.block_on(self.client.parse_query(query))
+    }
+
+    /// Create a [`SeriesQueryBuilder`] to find time series by series selectors, see [`crate::Client::series`].
+    pub fn series<T>(&self, selectors: T) -> Result<SeriesQueryBuilder, Error>
+    where
+        T: IntoIterator,
+        T::Item: Borrow<Selector>,
+    {
+        Ok(self.wrap(self.client.series(selectors)?))
+    }
+
+    /// Create a [`LabelNamesQueryBuilder`] to retrieve label names, see [`crate::Client::label_names`].
+    pub fn label_names(&self) -> LabelNamesQueryBuilder {
+        self.wrap(self.client.label_names())
+    }
+
+    /// Create a [`LabelValuesQueryBuilder`] to retrieve the values of a label, see [`crate::Client::label_values`].
+    pub fn label_values(&self, label: impl std::fmt::Display) -> LabelValuesQueryBuilder {
+        self.wrap(self.client.label_values(label))
+    }
+
+    /// Query the current state of target discovery, see [`crate::Client::targets`].
+    pub fn targets(&self, state: Option<TargetState>) -> Result<Targets, Error> {
+        self.block_on(self.client.targets(state))
+    }
+
+    /// Create a [`RulesQueryBuilder`] to retrieve alerting and recording rules, see [`crate::Client::rules`].
+    pub fn rules(&self) -> RulesQueryBuilder {
+        self.wrap(self.client.rules())
+    }
+
+    /// Retrieve all active alerts, see [`crate::Client::alerts`].
+    pub fn alerts(&self) -> Result<Vec<Alert>, Error> {
+        self.block_on(self.client.alerts())
+    }
+
+    /// Retrieve the flag values that Prometheus was configured with, see [`crate::Client::flags`].
+    pub fn flags(&self) -> Result<HashMap<String, String>, Error> {
+        self.block_on(self.client.flags())
+    }
+
+    /// Retrieve Prometheus server build information, see [`crate::Client::build_information`].
+    pub fn build_information(&self) -> Result<BuildInformation, Error> {
+        self.block_on(self.client.build_information())
+    }
+
+    /// Retrieve Prometheus server runtime information, see [`crate::Client::runtime_information`].
+    pub fn runtime_information(&self) -> Result<RuntimeInformation, Error> {
+        self.block_on(self.client.runtime_information())
+    }
+
+    /// Retrieve the currently loaded configuration, see [`crate::Client::config`].
+    pub fn config(&self) -> Result<Config, Error> {
+        self.block_on(self.client.config())
+    }
+
+    /// Retrieve Prometheus TSDB statistics, see [`crate::Client::tsdb_statistics`].
+    pub fn tsdb_statistics(&self) -> Result<TsdbStatistics, Error> {
+        self.block_on(self.client.tsdb_statistics())
+    }
+
+    /// Retrieve WAL replay statistics, see [`crate::Client::wal_replay_statistics`].
+    pub fn wal_replay_statistics(&self) -> Result<WalReplayStatistics, Error> {
+        self.block_on(self.client.wal_replay_statistics())
+    }
+
+    /// Query the current state of alertmanager discovery, see [`crate::Client::alertmanagers`].
+    pub fn alertmanagers(&self) -> Result<Alertmanagers, Error> {
+        self.block_on(self.client.alertmanagers())
+    }
+
+    /// Create a [`TargetMetadataQueryBuilder`] to retrieve metric metadata of targets,
+    /// see [`crate::Client::target_metadata`].
+    pub fn target_metadata(&self) -> TargetMetadataQueryBuilder {
+        self.wrap(self.client.target_metadata())
+    }
+
+    /// Create a [`MetricMetadataQueryBuilder`] to retrieve metric metadata,
+    /// see [`crate::Client::metric_metadata`].
+    pub fn metric_metadata(&self) -> MetricMetadataQueryBuilder {
+        self.wrap(self.client.metric_metadata())
+    }
+
+    /// Create a snapshot of all current data, see [`crate::Client::snapshot`].
+    pub fn snapshot(&self, skip_head: bool) -> Result<Snapshot, Error> {
+        self.block_on(self.client.snapshot(skip_head))
+    }
+
+    /// Create a [`DeleteSeriesQueryBuilder`] to delete time series, see [`crate::Client::delete_series`].
+    pub fn delete_series<T>(&self, selectors: T) -> Result<DeleteSeriesQueryBuilder, Error>
+    where
+        T: IntoIterator,
+        T::Item: Borrow<Selector>,
+    {
+        Ok(self.wrap(self.client.delete_series(selectors)?))
+    }
+
+    /// Remove deleted data from disk, see [`crate::Client::clean_tombstones`].
+    pub fn clean_tombstones(&self) -> Result<(), Error> {
+        self.block_on(self.client.clean_tombstones())
+    }
+
+    /// Check Prometheus server health, see [`crate::Client::is_server_healthy`].
+    pub fn is_server_healthy(&self) -> Result<bool, Error> {
+        self.block_on(self.client.is_server_healthy())
+    }
+
+    /// Check Prometheus server readiness, see [`crate::Client::is_server_ready`].
+    pub fn is_server_ready(&self) -> Result<bool, Error> {
+        self.block_on(self.client.is_server_ready())
+    }
+}
+
+impl Default for Client {
+    /// Create a standard Client that sends requests to "http://127.0.0.1:9090/".
+    fn default() -> Self {
+        ClientBuilder::default().build_blocking().unwrap()
+    }
+}
+
+impl std::str::FromStr for Client {
+    type Err = crate::error::Error;
+
+    /// Create a Client from a custom base URL. Note that the API-specific
+    /// path segments (like `/api/v1/query`) are added automatically.
+    fn from_str(url: &str) -> Result<Self, Self::Err> {
+        ClientBuilder::default().url(url).build_blocking()
+    }
+}
+
+impl std::convert::TryFrom<&str> for Client {
+    type Error = crate::error::Error;
+
+    /// Create a [`Client`] from a custom base URL. Note that the API-specific
+    /// path segments (like `/api/v1/query`) are added automatically.
+    fn try_from(url: &str) -> Result<Self, Self::Error> {
+        ClientBuilder::default().url(url).build_blocking()
+    }
+}
+
+impl std::convert::TryFrom<String> for Client {
+    type Error = crate::error::Error;
+
+    /// Create a [`Client`] from a custom base URL. Note that the API-specific
+    /// path segments (like `/api/v1/query`) are added automatically.
+    fn try_from(url: String) -> Result<Self, Self::Error> {
+        ClientBuilder::default().url(url).build_blocking()
+    }
+}
+
+impl ClientBuilder {
+    /// Create a [`blocking::Client`](Client) (requires the `blocking` feature).
+    /// Fails if the base URL is invalid or the runtime that drives the requests cannot be created.
+    pub fn build_blocking(self) -> Result<Client, Error> {
+        Client::new(self.build()?)
+    }
+}
+
+/// Wraps one of the asynchronous query builders and executes the query on the
+/// runtime of the [`Client`] that created it.
+#[derive(Clone)]
+pub struct Builder<T> {
+    inner: T,
+    runtime: Arc<Runtime>,
+}
+
+impl<T> Builder<T> {
+    fn map(mut self, f: impl FnOnce(T) -> T) -> Self {
+        self.inner = f(self.inner);
+        self
+    }
+
+    fn block_on<F: Future>(self, f: impl FnOnce(T) -> F) -> F::Output {
+        self.runtime.block_on(f(self.inner))
+    }
+
+    fn block_on_stream<I, F>(self, f: impl FnOnce(T) -> F) -> Result<SeriesIter<I>, Error>
+    where
+        F: Future<Output = Result<SeriesStream<I>, Error>>,
+    {
+        let stream = self.runtime.block_on(f(self.inner))?;
+        Ok(SeriesIter {
+            stream,
+            runtime: self.runtime,
+        })
+    }
+}
+
+/// An iterator over time series that are parsed one at a time while the response is received,
+/// see [`crate::SeriesStream`].
+pub struct SeriesIter<T> {
+    stream: SeriesStream<T>,
+    runtime: Arc<Runtime>,
+}
+
+impl<T> SeriesIter<T> {
+    /// Return the warnings and infos that Prometheus attached to the response once the
+    /// iterator is exhausted, see [`crate::SeriesStream::annotations`].
+    pub fn annotations(&self) -> Option<&Annotations> {
+        self.stream.annotations()
+    }
+}
+
+impl<T: DeserializeOwned> Iterator for SeriesIter<T> {
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.runtime.block_on(self.stream.next())
+    }
+}
+
+/// Provides a builder to set some query parameters in the context
+/// of an instant query, see [`crate::InstantQueryBuilder`].
+pub type InstantQueryBuilder = Builder<crate::InstantQueryBuilder>;
+
+impl InstantQueryBuilder {
+    /// Set the evaluation timestamp, see [`crate::InstantQueryBuilder::at`].
+    pub fn at(self, time: impl ToTimestamp) -> Self {
+        self.map(|b| b.at(time))
+    }
+
+    /// Set the evaluation timeout, see [`crate::InstantQueryBuilder::timeout`].
+    pub fn timeout(self, timeout: impl ToQueryDuration) -> Self {
+        self.map(|b| b.timeout(timeout))
+    }
+
+    /// Instruct Prometheus to compile query statistics as part of the API response.
+    pub fn stats(self) -> Self {
+        self.map(|b| b.stats())
+    }
+
+    /// Include an additional header to the request.
+    pub fn header<K: IntoHeaderName, V: Into<HeaderValue>>(self, name: K, value: V) -> Self {
+        self.map(|b| b.header(name, value))
+    }
+
+    /// Include an additional parameter to the request.
+    pub fn query(self, name: &'static str, value: impl ToString) -> Self {
+        self.map(|b| b.query(name, value))
+    }
+
+    /// Execute the instant query (using HTTP GET) and return the parsed API response.
+    pub fn get(self) -> Result<PromqlResult, Error> {
+        self.block_on(|b| b.get())
+    }
+
+    /// Execute the instant query (using HTTP POST) and return the parsed API response.
+    pub fn post(self) -> Result<PromqlResult, Error> {
+        self.block_on(|b| b.post())
+    }
+
+    /// Execute the instant query (using HTTP GET) and return an iterator over the resulting
+    /// time series, see [`crate::InstantQueryBuilder::get_stream`].
+    pub fn get_stream(self) -> Result<SeriesIter<InstantVector>, Error> {
+        self.block_on_stream(|b| b.get_stream())
+    }
+
+    /// Execute the instant query (using HTTP POST) and return an iterator over the resulting
+    /// time series, see [`crate::InstantQueryBuilder::post_stream`].
+    pub fn post_stream(self) -> Result<SeriesIter<InstantVector>, Error> {
+        self.block_on_stream(|b| b.post_stream())
+    }
+}
+
+/// Provides a builder to set some query parameters in the context
+/// of a range query, see [`crate::RangeQueryBuilder`].
+pub type RangeQueryBuilder = Builder<crate::RangeQueryBuilder>;
+
+impl RangeQueryBuilder {
+    /// Set the evaluation timeout, see [`crate::RangeQueryBuilder::timeout`].
+    pub fn timeout(self, timeout: impl ToQueryDuration) -> Self {
+        self.map(|b| b.timeout(timeout))
+    }
+
+    /// Split the range query into several queries, see [`crate::RangeQueryBuilder::split`].
+    pub fn split(self, max_points: u64) -> Self {
+        self.map(|b| b.split(max_points))
+    }
+
+    /// Set the maximum number of queries that are executed concurrently when the range
+    /// query is split, see [`crate::RangeQueryBuilder::split_concurrency`].
+    pub fn split_concurrency(self, concurrency: usize) -> Self {
+        self.map(|b| b.split_concurrency(concurrency))
+    }
+
+    /// Instruct Prometheus to compile query statistics as part of the API response.
+    pub fn stats(self) -> Self {
+        self.map(|b| b.stats())
+    }
+
+    /// Include an additional header to the request.
+    pub fn header<K: IntoHeaderName, V: Into<HeaderValue>>(self, name: K, value: V) -> Self {
+        self.map(|b| b.header(name, value))
+    }
+
+    /// Include an additional parameter to the request.
+    pub fn query(self, name: &'static str, value: impl ToString) -> Self {
+        self.map(|b| b.query(name, value))
+    }
+
+    /// Execute the range query (using HTTP GET) and return the parsed API response.
+    pub fn get(self) -> Result<PromqlResult, Error> {
+        self.block_on(|b| b.get())
+    }
+
+    /// Execute the range query (using HTTP POST) and return the parsed API response.
+    pub fn post(self) -> Result<PromqlResult, Error> {
+        self.block_on(|b| b.post())
+    }
+
+    /// Execute the range query (using HTTP GET) and return an iterator over the resulting
+    /// time series, see [`crate::RangeQueryBuilder::get_stream`].
+    pub fn get_stream(self) -> Result<SeriesIter<RangeVector>, Error> {
+        self.block_on_stream(|b| b.get_stream())
+    }
+
+    /// Execute the range query (using HTTP POST) and return an iterator over the resulting
+    /// time series, see [`crate::RangeQueryBuilder::post_stream`].
+    pub fn post_stream(self) -> Result<SeriesIter<RangeVector>, Error> {
+        self.block_on_stream(|b| b.post_stream())
+    }
+
+    /// Execute the range query (using HTTP GET) and collect the resulting time series into a
+    /// [`ColumnarMatrix`], see [`crate::RangeQueryBuilder::get_columnar`].
+    pub fn get_columnar(self) -> Result<ColumnarMatrix, Error> {
+        self.block_on(|b| b.get_columnar())
+    }
+
+    /// Execute the range query (using HTTP POST) and collect the resulting time series into a
+    /// [`ColumnarMatrix`], see [`crate::RangeQueryBuilder::post_columnar`].
+    pub fn post_columnar(self) -> Result<ColumnarMatrix, Error> {
+        self.block_on(|b| b.post_columnar())
+    }
+}
+
+/// Provides a builder to set some query parameters in the context
+/// of an exemplar query, see [`crate::ExemplarsQueryBuilder`].
+pub type ExemplarsQueryBuilder = Builder<crate::ExemplarsQueryBuilder>;
+
+impl ExemplarsQueryBuilder {
+    /// Include an additional header to the request.
+    pub fn header<K: IntoHeaderName, V: Into<HeaderValue>>(self, name: K, value: V) -> Self {
+        self.map(|b| b.header(name, value))
+    }
+
+    /// Include an additional parameter to the request.
+    pub fn query(self, name: &'static str, value: impl ToString) -> Self {
+        self.map(|b| b.query(name, value))
+    }
+
+    /// Execute the exemplar query (using HTTP GET) and return the parsed API response.
+    pub fn get(self) -> Result<Vec<ExemplarSeries>, Error> {
+        self.block_on(|b| b.get())
+    }
+
+    /// Execute the exemplar query (using HTTP POST) and return the parsed API response.
+    pub fn post(self) -> Result<Vec<ExemplarSeries>, Error> {
+        self.block_on(|b| b.post())
+    }
+}
+
+/// Provides a builder to set some query parameters in the context
+/// of a rules query, see [`crate::RulesQueryBuilder`].
+pub type RulesQueryBuilder = Builder<crate::RulesQueryBuilder>;
+
+impl RulesQueryBuilder {
+    /// Set the kind of rules to retrieve, see [`crate::RulesQueryBuilder::kind`].
+    pub fn kind(self, kind: RuleKind) -> Self {
+        self.map(|b| b.kind(kind))
+    }
+
+    /// Only return rules whose names match one of the given names, see [`crate::RulesQueryBuilder::names`].
+    pub fn names<T>(self, names: T) -> Self
+    where
+        T: IntoIterator,
+        T::Item: std::fmt::Display,
+    {
+        self.map(|b| b.names(names))
+    }
+
+    /// Only return rules that match this name, see [`crate::RulesQueryBuilder::name`].
+    pub fn name(self, name: impl std::fmt::Display) -> Self {
+        self.map(|b| b.name(name))
+    }
+
+    /// Only return rules that are part of one of the given groups, see [`crate::RulesQueryBuilder::groups`].
+    pub fn groups<T>(self, groups: T) -> Self
+    where
+        T: IntoIterator,
+        T::Item: std::fmt::Display,
+    {
+        self.map(|b| b.groups(groups))
+    }
+
+    /// Only return rules that are part of this group, see [`crate::RulesQueryBuilder::group`].
+    pub fn group(self, group: impl std::fmt::Display) -> Self {
+        self.map(|b| b.group(group))
+    }
+
+    /// Only return rules that are loaded from one of the given files, see [`crate::RulesQueryBuilder::files`].
+    pub fn files<T>(self, files: T) -> Self
+    where
+        T: IntoIterator,
+        T::Item: std::fmt::Display,
+    {
+        self.map(|b| b.files(files))
+    }
+
+    /// Only return rules that are loaded from this file, see [`crate::RulesQueryBuilder::file`].
+    pub fn file(self, file: impl std::fmt::Display) -> Self {
+        self.map(|b| b.file(file))
+    }
+
+    /// Execute the rules query (using HTTP GET) and return the [`RuleGroup`]s sent by Prometheus.
+    pub fn get(self) -> Result<Vec<RuleGroup>, Error> {
+        self.block_on(|b| b.get())
+    }
+
+    /// Execute the rules query (using HTTP GET) and return the [`RuleGroup`]s along with any
+    /// [`Annotations`] sent by Prometheus.
+    pub fn get_with_annotations(self) -> Result<(Vec<RuleGroup>, Annotations), Error> {
+        self.block_on(|b| b.get_with_annotations())
+    }
+}
+
+/// Provides a builder to set some query parameters in the context
+/// of a target metadata query, see [`crate::TargetMetadataQueryBuilder`].
+pub type TargetMetadataQueryBuilder = Builder<crate::TargetMetadataQueryBuilder>;
+
+impl TargetMetadataQueryBuilder {
+    /// Select the targets to retrieve metadata from, see [`crate::TargetMetadataQueryBuilder::match_target`].
+    pub fn match_target(self, selector: &Selector) -> Self {
+        self.map(|b| b.match_target(selector))
+    }
+
+    /// Only retrieve metadata of this metric, see [`crate::TargetMetadataQueryBuilder::metric`].
+    pub fn metric(self, metric: impl std::fmt::Display) -> Self {
+        self.map(|b| b.metric(metric))
+    }
+
+    /// Limit the number of targets to match, see [`crate::TargetMetadataQueryBuilder::limit`].
+    pub fn limit(self, limit: i32) -> Self {
+        self.map(|b| b.limit(limit))
+    }
+
+    /// Execute the target metadata query (using HTTP GET) and return the collection of
+    /// [`TargetMetadata`] sent by Prometheus.
+    pub fn get(self) -> Result<Vec<TargetMetadata>, Error> {
+        self.block_on(|b| b.get())
+    }
+
+    /// Execute the target metadata query (using HTTP GET) and return the collection of
+    /// [`TargetMetadata`] along with any [`Annotations`] sent by Prometheus.
+    pub fn get_with_annotations(self) -> Result<(Vec<TargetMetadata>, Annotations), Error> {
+        self.block_on(|b| b.get_with_annotations())
+    }
+}
+
+/// Provides a builder to set some query parameters in the context
+/// of a metric metadata query, see [`crate::MetricMetadataQueryBuilder`].
+pub type MetricMetadataQueryBuilder = Builder<crate::MetricMetadataQueryBuilder>;
+
+impl MetricMetadataQueryBuilder {
+    /// Only retrieve metadata of this metric, see [`crate::MetricMetadataQueryBuilder::metric`].
+    pub fn metric(self, metric: impl std::fmt::Display) -> Self {
+        self.map(|b| b.metric(metric))
+    }
+
+    /// Limit the number of metrics to return, see [`crate::MetricMetadataQueryBuilder::limit`].
+    pub fn limit(self, limit: i32) -> Self {
+        self.map(|b| b.limit(limit))
+    }
+
+    /// Limit the number of metadata entries per metric, see [`crate::MetricMetadataQueryBuilder::limit_per_metric`].
+    pub fn limit_per_metric(self, limit_per_metric: i32) -> Self {
+        self.map(|b| b.limit_per_metric(limit_per_metric))
+    }
+
+    /// Execute the metric metadata query (using HTTP GET) and return the collection of
+    /// [`MetricMetadata`] sent by Prometheus.
+    pub fn get(self) -> Result<HashMap<String, Vec<MetricMetadata>>, Error> {
+        self.block_on(|b| b.get())
+    }
+
+    /// Execute the metric metadata query (using HTTP GET) and return the collection of
+    /// [`MetricMetadata`] along with any [`Annotations`] sent by Prometheus.
+    pub fn get_with_annotations(
+        self,
+    ) -> Result<(HashMap<String, Vec<MetricMetadata>>, Annotations), Error> {
+        self.block_on(|b| b.get_with_annotations())
+    }
+}
+
+/// Provides a builder to set some query parameters in the context
+/// of a series metadata query, see [`crate::SeriesQueryBuilder`].
+pub type SeriesQueryBuilder = Builder<crate::SeriesQueryBuilder>;
+
+impl SeriesQueryBuilder {
+    /// Set the start time (e.g. a Unix timestamp in seconds), see [`crate::SeriesQueryBuilder::start`].
+    pub fn start(self, start: impl ToTimestamp) -> Self {
+        self.map(|b| b.start(start))
+    }
+
+    /// Set the end time (e.g. a Unix timestamp in seconds), see [`crate::SeriesQueryBuilder::end`].
+    pub fn end(self, end: impl ToTimestamp) -> Self {
+        self.map(|b| b.end(end))
+    }
+
+    /// Execute the series metadata query (using HTTP GET) and return the time series sent by Prometheus.
+    pub fn get(self) -> Result<Vec<HashMap<String, String>>, Error> {
+        self.block_on(|b| b.get())
+    }
+
+    /// Execute the series metadata query (using HTTP GET) and return the time series along with
+    /// any [`Annotations`] sent by Prometheus.
+    pub fn get_with_annotations(
+        self,
+    ) -> Result<(Vec<HashMap<String, String>>, Annotations), Error> {
+        self.block_on(|b| b.get_with_annotations())
+    }
+
+    /// Execute the series metadata query (using HTTP GET) and pass each matching time series
+    /// to the given closure, see [`crate::SeriesQueryBuilder::for_each`].
+    pub fn for_each<F>(self, f: F) -> Result<(), Error>
+    where
+        F: FnMut(HashMap<String, String>),
+    {
+        self.block_on(|b| b.for_each(f))
+    }
+}
+
+/// Provides a builder to set some query parameters in the context
+/// of a delete series request, see [`crate::DeleteSeriesQueryBuilder`].
+pub type DeleteSeriesQueryBuilder = Builder<crate::DeleteSeriesQueryBuilder>;
+
+impl DeleteSeriesQueryBuilder {
+    /// Set the start time (e.g. a Unix timestamp in seconds), see [`crate::DeleteSeriesQueryBuilder::start`].
+    pub fn start(self, start: impl ToTimestamp) -> Self {
+        self.map(|b| b.start(start))
+    }
+
+    /// Set the end time (e.g. a Unix timestamp in seconds), see [`crate::DeleteSeriesQueryBuilder::end`].
+    pub fn end(self, end: impl ToTimestamp) -> Self {
+        self.map(|b| b.end(end))
+    }
+
+    /// Execute the request (using HTTP POST).
+    pub fn post(self) -> Result<(), Error> {
+        self.block_on(|b| b.post())
+    }
+}
+
+/// Provides a builder to set some query parameters in the context
+/// of a label names query, see [`crate::LabelNamesQueryBuilder`].
+pub type LabelNamesQueryBuilder = Builder<crate::LabelNamesQueryBuilder>;
+
+impl LabelNamesQueryBuilder {
+    /// Set series selectors to filter the time series from which Prometheus reads labels,
+    /// see [`crate::LabelNamesQueryBuilder::selectors`].
+    pub fn selectors<T>(self, selectors: T) -> Self
+    where
+        T: IntoIterator,
+        T::Item: Borrow<Selector>,
+    {
+        self.map(|b| b.selectors(selectors))
+    }
+
+    /// Set the start time (e.g. a Unix timestamp in seconds), see [`crate::LabelNamesQueryBuilder::start`].
+    pub fn start(self, start: impl ToTimestamp) -> Self {
+        self.map(|b| b.start(start))
+    }
+
+    /// Set the end time (e.g. a Unix timestamp in seconds), see [`crate::LabelNamesQueryBuilder::end`].
+    pub fn end(self, end: impl ToTimestamp) -> Self {
+        self.map(|b| b.end(end))
+    }
+
+    /// Execute the label names query (using HTTP GET) and return the label names sent by Prometheus.
+    pub fn get(self) -> Result<Vec<String>, Error> {
+        self.block_on(|b| b.get())
+    }
+
+    /// Execute the label names query (using HTTP GET) and return the label names along with
+    /// any [`Annotations`] sent by Prometheus.
+    pub fn get_with_annotations(self) -> Result<(Vec<String>, Annotations), Error> {
+        self.block_on(|b| b.get_with_annotations())
+    }
+}
+
+/// Provides a builder to set some query parameters in the context
+/// of a label values query, see [`crate::LabelValuesQueryBuilder`].
+pub type LabelValuesQueryBuilder = Builder<crate::LabelValuesQueryBuilder>;
+
+impl LabelValuesQueryBuilder {
+    /// Set series selectors to filter the time series from which Prometheus reads label values,
+    /// see [`crate::LabelValuesQueryBuilder::selectors`].
+    pub fn selectors<T>(self, selectors: T) -> Self
+    where
+        T: IntoIterator,
+        T::Item: Borrow<Selector>,
+    {
+        self.map(|b| b.selectors(selectors))
+    }
+
+    /// Set the start time (e.g. a Unix timestamp in seconds), see [`crate::LabelValuesQueryBuilder::start`].
+    pub fn start(self, start: impl ToTimestamp) -> Self {
+        self.map(|b| b.start(start))
+    }
+
+    /// Set the end time (e.g. a Unix timestamp in seconds), see [`crate::LabelValuesQueryBuilder::end`].
+    pub fn end(self, end: impl ToTimestamp) -> Self {
+        self.map(|b| b.end(end))
+    }
+
+    /// Execute the label values query (using HTTP GET) and return the label values sent by Prometheus.
+    pub fn get(self) -> Result<Vec<String>, Error> {
+        self.block_on(|b| b.get())
+    }
+
+    /// Execute the label values query (using HTTP GET) and return the label values along with
+    /// any [`Annotations`] sent by Prometheus.
+    pub fn get_with_annotations(self) -> Result<(Vec<String>, Annotations), Error> {
+        self.block_on(|b| b.get_with_annotations())
+    }
+
+    /// Execute the label values query (using HTTP GET) and pass each label value to the given
+    /// closure, see [`crate::LabelValuesQueryBuilder::for_each`].
+    pub fn for_each<F>(self, f: F) -> Result<(), Error>
+    where
+        F: FnMut(String),
+    {
+        self.block_on(|b| b.for_each(f))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{json_response, raw_response, serve, StaticTransport};
+    use crate::HttpRequest;
+
+    #[test]
+    fn test_blocking_query() -> Result<(), anyhow::Error> {
+        let (url, server) = serve(vec![raw_response(
+            "200 OK",
+            r#"{"status":"success","data":{"resultType":"scalar","result":[1,"42"]}}"#,
+        )]);
+
+        let client = Client::try_from(url)?;
+        let response = client.query("42").at(1).get()?;
+
+        assert_eq!(response.data().as_scalar().unwrap().value(), 42.0);
+
+        let requests = server.join().unwrap();
+        assert!(requests[0].starts_with("get /api/v1/query?query=42&time=1 "));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_blocking_stream() -> Result<(), anyhow::Error> {
+        let transport = StaticTransport::new(|_: &HttpRequest| {
+            Ok(json_response(
+                r#"{"status":"success","data":{"resultType":"vector","result":[{"metric":{"job":"a"},"value":[1,"1"]},{"metric":{"job":"b"},"value":[1,"0"]}]},"infos":["info"]}"#,
+            ))
+        });
+
+        let client = crate::Client::builder()
+            .transport(transport)
+            .build_blocking()?;
+
+        let mut iter = client.query("up").get_stream()?;
+        let jobs = iter
+            .by_ref()
+            .map(|v| v.map(|v| v.metric()["job"].clone()))
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(jobs, ["a", "b"]);
+        assert_eq!(iter.annotations().unwrap().infos(), ["info"]);
+
+        Ok(())
+    }
+}
@@ -1,14 +1,44 @@
-use crate::error::{ClientError, Error};
+use crate::arg::{QueryDuration, Timestamp, ToQueryDuration, ToTimestamp};
+use crate::ast::Node;
+use crate::auth::Auth;
+use crate::error::{ClientError, Error, TransportError};
+use crate::middleware::{Middleware, MiddlewareRequest, MiddlewareResponse};
+use crate::multi::merge_results;
 use crate::response::*;
+use crate::retry::RetryPolicy;
 use crate::selector::Selector;
+use crate::stream::{SeriesStream, Target};
+use crate::transport::{HttpRequest, HttpResponse, Transport};
 use crate::util::{self, build_final_url, RuleKind, TargetState, ToBaseUrl};
-use reqwest::header::{HeaderMap, HeaderValue, IntoHeaderName, CONTENT_TYPE};
+use futures_util::{stream, StreamExt, TryStreamExt};
+use reqwest::header::{
+    HeaderMap, HeaderValue, IntoHeaderName, AUTHORIZATION, CONTENT_TYPE, USER_AGENT,
+};
 use reqwest::Method as HttpMethod;
-use serde::{de::DeserializeOwned, Serialize};
+use reqwest::StatusCode;
+use serde::{
+    de::{DeserializeOwned, IgnoredAny},
+    Serialize,
+};
 use std::borrow::Borrow;
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use url::Url;
 
+// Prometheus rejects range queries that return more points per series.
+const MAX_POINTS: i64 = 11_000;
+
+// Endpoints that accept POST requests without modifying any data on the server.
+const READ_ONLY_ENDPOINTS: [&str; 5] = [
+    "api/v1/query",
+    "api/v1/query_range",
+    "api/v1/query_exemplars",
+    "api/v1/series",
+    "api/v1/labels",
+];
+
 /// Provides a builder to set some query parameters in the context
 /// of an instant query before sending it to Prometheus.
 #[derive(Clone)]
@@ -19,20 +49,21 @@ pub struct InstantQueryBuilder {
 }
 
 impl InstantQueryBuilder {
-    /// Set the evaluation timestamp (Unix timestamp in seconds, e.g. 1659182624).
+    /// Set the evaluation timestamp (e.g. a Unix timestamp in seconds like 1659182624, see [`ToTimestamp`]).
     /// If this is not set the evaluation timestamp will default to the current Prometheus
     /// server time.
     /// See also: [Prometheus API documentation](https://prometheus.io/docs/prometheus/latest/querying/api/#instant-queries)
-    pub fn at(mut self, time: i64) -> Self {
-        self.params.push(("time", time.to_string()));
+    pub fn at(mut self, time: impl ToTimestamp) -> Self {
+        self.params.push(("time", time.to_timestamp().to_string()));
         self
     }
 
-    /// Set the evaluation timeout (milliseconds, e.g. 1000).
+    /// Set the evaluation timeout (e.g. `std::time::Duration::from_secs(1)` or `"1s"`, see [`ToQueryDuration`]).
     /// If this is not set the timeout will default to the value of the "-query.timeout" flag of the Prometheus server.
     /// See also: [Prometheus API documentation](https://prometheus.io/docs/prometheus/latest/querying/api/#instant-queries)
-    pub fn timeout(mut self, timeout: i64) -> Self {
-        self.params.push(("timeout", format!("{}ms", timeout)));
+    pub fn timeout(mut self, timeout: impl ToQueryDuration) -> Self {
+        self.params
+            .push(("timeout", timeout.to_query_duration().to_string()));
         self
     }
 
@@ -59,7 +90,7 @@ impl InstantQueryBuilder {
     /// Execute the instant query (using HTTP GET) and return the parsed API response.
     pub async fn get(self) -> Result<PromqlResult, Error> {
         let response = self.get_raw().await?;
-        Client::deserialize(response).await
+        Client::deserialize_promql(response).await
     }
 
     /// Execute the instant query (using HTTP POST) and return the parsed API response.
@@ -68,7 +99,23 @@ impl InstantQueryBuilder {
     /// character limits.
     pub async fn post(self) -> Result<PromqlResult, Error> {
         let response = self.post_raw().await?;
-        Client::deserialize(response).await
+        Client::deserialize_promql(response).await
+    }
+
+    /// Execute the instant query (using HTTP GET) and return a stream of the resulting time series,
+    /// which are parsed one at a time while the response is received (see [`SeriesStream`]).
+    /// The stream returns an [`Error::Deserialize`] if the query does not evaluate to an instant vector.
+    pub async fn get_stream(self) -> Result<SeriesStream<InstantVector>, Error> {
+        let response = self.get_raw().await?;
+        Client::stream(response, Target::Result("vector"))
+    }
+
+    /// Execute the instant query (using HTTP POST) and return a stream of the resulting time series,
+    /// which are parsed one at a time while the response is received (see [`SeriesStream`]).
+    /// The stream returns an [`Error::Deserialize`] if the query does not evaluate to an instant vector.
+    pub async fn post_stream(self) -> Result<SeriesStream<InstantVector>, Error> {
+        let response = self.post_raw().await?;
+        Client::stream(response, Target::Result("vector"))
     }
 
     /// Execute the instant query (using HTTP GET) and return the raw API response.
@@ -96,14 +143,18 @@ pub struct RangeQueryBuilder {
     client: Client,
     params: Vec<(&'static str, String)>,
     headers: Option<HeaderMap<HeaderValue>>,
+    range: (Timestamp, Timestamp, QueryDuration),
+    split: Option<u64>,
+    split_concurrency: usize,
 }
 
 impl RangeQueryBuilder {
-    /// Set the evaluation timeout (milliseconds, e.g. 1000).
+    /// Set the evaluation timeout (e.g. `std::time::Duration::from_secs(1)` or `"1s"`, see [`ToQueryDuration`]).
     /// If this is not set the timeout will default to the value of the "-query.timeout" flag of the Prometheus server.
     /// See also: [Prometheus API documentation](https://prometheus.io/docs/prometheus/latest/querying/api/#range-queries)
-    pub fn timeout(mut self, timeout: i64) -> Self {
-        self.params.push(("timeout", format!("{}ms", timeout)));
+    pub fn timeout(mut self, timeout: impl ToQueryDuration) -> Self {
+        self.params
+            .push(("timeout", timeout.to_query_duration().to_string()));
         self
     }
 
@@ -127,10 +178,56 @@ impl RangeQueryBuilder {
         self
     }
 
+    /// Split the range query into several queries that return at most the given number of
+    /// points per series each, e.g. in order to stay below Prometheus' limit of 11,000 points per series
+    /// or the query timeout. The queries cover consecutive step-aligned parts of the original interval,
+    /// are executed concurrently (see [`RangeQueryBuilder::split_concurrency`]) and their results are
+    /// stitched together by label set.
+    ///
+    /// Splitting only applies to [`RangeQueryBuilder::get`] and [`RangeQueryBuilder::post`]. Query statistics
+    /// are only returned for the first part. The range is not split if the start, end or step cannot be
+    /// converted to seconds (e.g. a timestamp string that is not a valid RFC 3339 timestamp).
+    /// Calling this repeatedly will replace the current setting.
+    ///
+    /// ```rust
+    /// use prometheus_http_query::Client;
+    ///
+    /// #[tokio::main(flavor = "current_thread")]
+    /// async fn main() -> Result<(), anyhow::Error> {
+    ///     let client = Client::default();
+    ///
+    ///     // One week at a step of 15 seconds, i.e. 40,321 points per series.
+    ///     let response = client
+    ///         .query_range("up", 1648373100, 1648977900, 15.0)
+    ///         .split(10000)
+    ///         .get()
+    ///         .await?;
+    ///
+    ///     assert!(response.data().as_matrix().is_some());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn split(mut self, max_points: u64) -> Self {
+        self.split = Some(max_points.max(1));
+        self
+    }
+
+    /// Set the maximum number of queries that are executed concurrently when the range
+    /// query is split (defaults to 4), see [`RangeQueryBuilder::split`].
+    /// Calling this repeatedly will replace the current setting.
+    pub fn split_concurrency(mut self, concurrency: usize) -> Self {
+        self.split_concurrency = concurrency.max(1);
+        self
+    }
+
     /// Execute the range query (using HTTP GET) and return the parsed API response.
     pub async fn get(self) -> Result<PromqlResult, Error> {
+        let chunks = self.chunks();
+        if !chunks.is_empty() {
+            return self.execute_split(chunks, HttpMethod::GET).await;
+        }
         let response = self.get_raw().await?;
-        Client::deserialize(response).await
+        Client::deserialize_promql(response).await
     }
 
     /// Execute the instant query (using HTTP POST) and return the parsed API response.
@@ -138,8 +235,159 @@ impl RangeQueryBuilder {
     /// the size of the final URL may break Prometheus' or an intermediate proxies' URL
     /// character limits.
     pub async fn post(self) -> Result<PromqlResult, Error> {
+        let chunks = self.chunks();
+        if !chunks.is_empty() {
+            return self.execute_split(chunks, HttpMethod::POST).await;
+        }
         let response = self.post_raw().await?;
-        Client::deserialize(response).await
+        Client::deserialize_promql(response).await
+    }
+
+    // Return the start and end time of each part of the split range query or nothing
+    // if the range cannot be split (e.g. because the step is not a valid duration).
+    fn chunks(&self) -> Vec<(f64, f64)> {
+        let (start, end, step) = match (
+            self.range.0.seconds(),
+            self.range.1.seconds(),
+            self.range.2.seconds(),
+        ) {
+            (Some(start), Some(end), Some(step)) => (start, end, step),
+            _ => return vec![],
+        };
+
+        let max_points = match self.split {
+            Some(max_points) if step > 0.0 && end >= start => max_points,
+            _ => return vec![],
+        };
+
+        let points = ((end - start) / step).floor() as u64 + 1;
+
+        (0..points)
+            .step_by(max_points as usize)
+            .map(|first| {
+                let last = (first + max_points).min(points) - 1;
+                (start + first as f64 * step, start + last as f64 * step)
+            })
+            .collect()
+    }
+
+    async fn execute_split(
+        self,
+        chunks: Vec<(f64, f64)>,
+        method: HttpMethod,
+    ) -> Result<PromqlResult, Error> {
+        let requests = chunks.into_iter().map(|(start, end)| {
+            let params: Vec<(&'static str, String)> = self
+                .params
+                .iter()
+                .map(|(k, v)| match *k {
+                    "start" => (*k, start.to_string()),
+                    "end" => (*k, end.to_string()),
+                    _ => (*k, v.clone()),
+                })
+                .collect();
+            let client = self.client.clone();
+            let method = method.clone();
+            let headers = self.headers.clone();
+
+            async move {
+                let response = client
+                    .send("api/v1/query_range", &params, method, headers)
+                    .await?;
+                Client::deserialize_promql(response).await
+            }
+        });
+
+        let results: Vec<PromqlResult> = stream::iter(requests)
+            .buffered(self.split_concurrency)
+            .try_collect()
+            .await?;
+
+        Ok(merge_results(results))
+    }
+
+    /// Execute the range query (using HTTP GET) and return a stream of the resulting time series,
+    /// which are parsed one at a time while the response is received (see [`SeriesStream`]).
+    /// Note that the range is not split (see [`RangeQueryBuilder::split`]).
+    pub async fn get_stream(self) -> Result<SeriesStream<RangeVector>, Error> {
+        let response = self.get_raw().await?;
+        Client::stream(response, Target::Result("matrix"))
+    }
+
+    /// Execute the range query (using HTTP POST) and return a stream of the resulting time series,
+    /// which are parsed one at a time while the response is received (see [`SeriesStream`]).
+    /// Note that the range is not split (see [`RangeQueryBuilder::split`]).
+    pub async fn post_stream(self) -> Result<SeriesStream<RangeVector>, Error> {
+        let response = self.post_raw().await?;
+        Client::stream(response, Target::Result("matrix"))
+    }
+
+    /// Execute the range query (using HTTP GET) and collect the resulting time series into a
+    /// [`ColumnarMatrix`] while the response is received, i.e. without holding all series as
+    /// [`RangeVector`]s in memory (see [`RangeQueryBuilder::get_stream`]).
+    pub async fn get_columnar(self) -> Result<ColumnarMatrix, Error> {
+        let steps = self.steps();
+        let stream = self.get_stream().await?;
+        Self::collect_columnar(stream, steps).await
+    }
+
+    /// Execute the range query (using HTTP POST) and collect the resulting time series into a
+    /// [`ColumnarMatrix`] while the response is received, i.e. without holding all series as
+    /// [`RangeVector`]s in memory (see [`RangeQueryBuilder::post_stream`]).
+    pub async fn post_columnar(self) -> Result<ColumnarMatrix, Error> {
+        let steps = self.steps();
+        let stream = self.post_stream().await?;
+        Self::collect_columnar(stream, steps).await
+    }
+
+    // Return the evaluation steps of the range query or nothing if the range cannot be
+    // determined or exceeds the number of points per series that Prometheus allows.
+    fn steps(&self) -> Vec<f64> {
+        // Prometheus evaluates range queries at millisecond precision.
+        let millis = |t: Option<f64>| t.map(|t| (t * 1000.0).round() as i64);
+
+        let (start, end, step) = match (
+            millis(self.range.0.seconds()),
+            millis(self.range.1.seconds()),
+            millis(self.range.2.seconds()),
+        ) {
+            (Some(start), Some(end), Some(step)) if step > 0 && end >= start => (start, end, step),
+            _ => return vec![],
+        };
+
+        if (end - start) / step >= MAX_POINTS {
+            return vec![];
+        }
+
+        (start..=end)
+            .step_by(step as usize)
+            .map(|t| t as f64 / 1000.0)
+            .collect()
+    }
+
+    // Collect the series into a matrix whose timestamps are the evaluation steps of the
+    // range query. Timestamps that are off this grid are merged in as they appear, in
+    // which case the steps without any sample are dropped again.
+    async fn collect_columnar(
+        mut stream: SeriesStream<RangeVector>,
+        steps: Vec<f64>,
+    ) -> Result<ColumnarMatrix, Error> {
+        let grid = steps.len();
+        let mut matrix = ColumnarMatrix::with_timestamps(steps);
+
+        while let Some(series) = stream.try_next().await? {
+            matrix.push(series);
+        }
+
+        if grid > 0 && matrix.timestamps().len() > grid {
+            matrix.retain_sampled_timestamps();
+        }
+
+        if let Some(annotations) = stream.annotations() {
+            matrix.annotations = annotations.clone();
+        }
+
+        Ok(matrix)
     }
 
     /// Execute the range query (using HTTP GET) and return the raw API response.
@@ -170,6 +418,73 @@ impl RangeQueryBuilder {
     }
 }
 
+/// Provides a builder to set some query parameters in the context
+/// of an exemplar query before sending it to Prometheus.
+#[derive(Clone)]
+pub struct ExemplarsQueryBuilder {
+    client: Client,
+    params: Vec<(&'static str, String)>,
+    headers: Option<HeaderMap<HeaderValue>>,
+}
+
+impl ExemplarsQueryBuilder {
+    /// Include an additional header to the request.
+    pub fn header<K: IntoHeaderName, T: Into<HeaderValue>>(mut self, name: K, value: T) -> Self {
+        self.headers
+            .get_or_insert_with(Default::default)
+            .append(name, value.into());
+        self
+    }
+
+    /// Include an additional parameter to the request.
+    pub fn query(mut self, name: &'static str, value: impl ToString) -> Self {
+        self.params.push((name, value.to_string()));
+        self
+    }
+
+    /// Execute the exemplar query (using HTTP GET) and return the parsed API response.
+    pub async fn get(self) -> Result<Vec<ExemplarSeries>, Error> {
+        let response = self.get_raw().await?;
+        Client::deserialize(response).await
+    }
+
+    /// Execute the exemplar query (using HTTP POST) and return the parsed API response.
+    /// Using a POST request is useful in the context of larger PromQL queries when
+    /// the size of the final URL may break Prometheus' or an intermediate proxies' URL
+    /// character limits.
+    pub async fn post(self) -> Result<Vec<ExemplarSeries>, Error> {
+        let response = self.post_raw().await?;
+        Client::deserialize(response).await
+    }
+
+    /// Execute the exemplar query (using HTTP GET) and return the raw API response.
+    pub async fn get_raw(self) -> Result<reqwest::Response, Error> {
+        self.client
+            .send(
+                "api/v1/query_exemplars",
+                &self.params,
+                HttpMethod::GET,
+                self.headers,
+            )
+            .await
+    }
+
+    /// Execute the exemplar query (using HTTP POST) and return the raw API response.
+    /// Using a POST request is useful in the context of larger PromQL queries when
+    /// the size of the final URL may break Prometheus' or an intermediate proxies' URL
+    /// character limits.
+    pub async fn post_raw(self) -> Result<reqwest::Response, Error> {
+        self.client
+            .send(
+                "api/v1/query_exemplars",
+                &self.params,
+                HttpMethod::POST,
+                self.headers,
+            )
+            .await
+    }
+}
+
 /// Provides methods to build a query to the rules endpoint and send it to Prometheus.
 #[derive(Clone)]
 pub struct RulesQueryBuilder {
@@ -263,6 +578,15 @@ impl RulesQueryBuilder {
             .map(|r: RuleGroups| r.groups)
     }
 
+    /// Execute the rules query (using HTTP GET) and return the [`RuleGroup`]s sent
+    /// by Prometheus along with any warnings and infos attached to the response.
+    pub async fn get_with_annotations(self) -> Result<(Vec<RuleGroup>, Annotations), Error> {
+        let response = self.get_raw().await?;
+        Client::deserialize_annotated(response)
+            .await
+            .map(|(r, annotations): (RuleGroups, Annotations)| (r.groups, annotations))
+    }
+
     /// Execute the rules query (using HTTP GET) and return the raw response sent
     /// by Prometheus.
     pub async fn get_raw(self) -> Result<reqwest::Response, Error> {
@@ -292,9 +616,9 @@ impl RulesQueryBuilder {
 
 /// Provides methods to build a query to the target metadata endpoint and send it to Prometheus.
 #[derive(Clone)]
-pub struct TargetMetadataQueryBuilder<'a> {
+pub struct TargetMetadataQueryBuilder {
     client: Client,
-    match_target: Option<Selector<'a>>,
+    match_target: Option<Selector>,
     metric: Option<String>,
     limit: Option<i32>,
 }
@@ -303,11 +627,11 @@ pub struct TargetMetadataQueryBuilder<'a> {
 /// and only returns target metadata that matches all filters.<br>
 /// See the official documentation for a thorough explanation on the filters that can
 /// be set: [Prometheus API documentation](https://prometheus.io/docs/prometheus/latest/querying/api/#querying-target-metadata).
-impl<'a> TargetMetadataQueryBuilder<'a> {
+impl TargetMetadataQueryBuilder {
     /// Pass a label selector to instruct Prometheus to filter targets by their label
     /// sets.
     /// Calling this repeatedly will replace the current label selector.
-    pub fn match_target(mut self, selector: &'a Selector<'a>) -> Self {
+    pub fn match_target(mut self, selector: &Selector) -> Self {
         self.match_target = Some(selector.clone());
         self
     }
@@ -333,6 +657,14 @@ impl<'a> TargetMetadataQueryBuilder<'a> {
         Client::deserialize(response).await
     }
 
+    /// Execute the target metadata query (using HTTP GET) and return the collection of
+    /// [`TargetMetadata`] sent by Prometheus along with any warnings and infos attached
+    /// to the response.
+    pub async fn get_with_annotations(self) -> Result<(Vec<TargetMetadata>, Annotations), Error> {
+        let response = self.get_raw().await?;
+        Client::deserialize_annotated(response).await
+    }
+
     /// Execute the target metadata query (using HTTP GET) and return the raw response
     /// sent by Prometheus.
     pub async fn get_raw(self) -> Result<reqwest::Response, Error> {
@@ -398,6 +730,16 @@ impl MetricMetadataQueryBuilder {
         Client::deserialize(response).await
     }
 
+    /// Execute the metric metadata query (using HTTP GET) and return the collection of
+    /// [`MetricMetadata`] sent by Prometheus along with any warnings and infos attached
+    /// to the response.
+    pub async fn get_with_annotations(
+        self,
+    ) -> Result<(HashMap<String, Vec<MetricMetadata>>, Annotations), Error> {
+        let response = self.get_raw().await?;
+        Client::deserialize_annotated(response).await
+    }
+
     /// Execute the metric metadata query (using HTTP GET) and return the raw response
     /// sent by Prometheus.
     pub async fn get_raw(self) -> Result<reqwest::Response, Error> {
@@ -426,24 +768,24 @@ impl MetricMetadataQueryBuilder {
 pub struct SeriesQueryBuilder {
     client: Client,
     selectors: Vec<(&'static str, String)>,
-    start: Option<i64>,
-    end: Option<i64>,
+    start: Option<Timestamp>,
+    end: Option<Timestamp>,
 }
 
 impl SeriesQueryBuilder {
     /// Limit the amount of metadata returned by setting a start time
-    /// (UNIX timestamp in seconds).
+    /// (e.g. a Unix timestamp in seconds, see [`ToTimestamp`]).
     /// Calling this repeatedly will replace the current setting.
-    pub fn start(mut self, start: i64) -> Self {
-        self.start = Some(start);
+    pub fn start(mut self, start: impl ToTimestamp) -> Self {
+        self.start = Some(start.to_timestamp());
         self
     }
 
     /// Limit the amount of metadata returned by setting an end time
-    /// (UNIX timestamp in seconds).
+    /// (e.g. a Unix timestamp in seconds, see [`ToTimestamp`]).
     /// Calling this repeatedly will replace the current setting.
-    pub fn end(mut self, end: i64) -> Self {
-        self.end = Some(end);
+    pub fn end(mut self, end: impl ToTimestamp) -> Self {
+        self.end = Some(end.to_timestamp());
         self
     }
 
@@ -454,6 +796,31 @@ impl SeriesQueryBuilder {
         Client::deserialize(response).await
     }
 
+    /// Execute the series metadata query (using HTTP GET) and return a collection of
+    /// matching time series sent by Prometheus along with any warnings and infos attached
+    /// to the response.
+    pub async fn get_with_annotations(
+        self,
+    ) -> Result<(Vec<HashMap<String, String>>, Annotations), Error> {
+        let response = self.get_raw().await?;
+        Client::deserialize_annotated(response).await
+    }
+
+    /// Execute the series metadata query (using HTTP GET) and pass each matching time series
+    /// to the given closure as soon as it has been parsed, i.e. without holding the entire
+    /// response in memory.
+    pub async fn for_each<F>(self, mut f: F) -> Result<(), Error>
+    where
+        F: FnMut(HashMap<String, String>),
+    {
+        let response = self.get_raw().await?;
+        let mut stream = Client::stream(response, Target::Data)?;
+        while let Some(series) = stream.try_next().await? {
+            f(series);
+        }
+        Ok(())
+    }
+
     /// Execute the series metadata query (using HTTP GET) and return the raw response
     /// sent by Prometheus.
     pub async fn get_raw(self) -> Result<reqwest::Response, Error> {
@@ -475,13 +842,71 @@ impl SeriesQueryBuilder {
     }
 }
 
+/// Provides methods to build a request to the delete series endpoint and send it to Prometheus.
+#[derive(Clone)]
+pub struct DeleteSeriesQueryBuilder {
+    client: Client,
+    selectors: Vec<(&'static str, String)>,
+    start: Option<Timestamp>,
+    end: Option<Timestamp>,
+}
+
+impl DeleteSeriesQueryBuilder {
+    /// Only delete data points that are newer than this start time
+    /// (e.g. a Unix timestamp in seconds, see [`ToTimestamp`]).
+    /// Calling this repeatedly will replace the current setting.
+    pub fn start(mut self, start: impl ToTimestamp) -> Self {
+        self.start = Some(start.to_timestamp());
+        self
+    }
+
+    /// Only delete data points that are older than this end time
+    /// (e.g. a Unix timestamp in seconds, see [`ToTimestamp`]).
+    /// Calling this repeatedly will replace the current setting.
+    pub fn end(mut self, end: impl ToTimestamp) -> Self {
+        self.end = Some(end.to_timestamp());
+        self
+    }
+
+    /// Execute the request (using HTTP POST) to delete all matching time series.
+    pub async fn post(self) -> Result<(), Error> {
+        let response = self.post_raw().await?;
+        Client::expect_no_content(response).await
+    }
+
+    /// Execute the request (using HTTP POST) to delete all matching time series
+    /// and return the raw response sent by Prometheus.
+    pub async fn post_raw(self) -> Result<reqwest::Response, Error> {
+        let mut params = vec![];
+
+        if let Some(start) = self.start {
+            params.push(("start", start.to_string()));
+        }
+
+        if let Some(end) = self.end {
+            params.push(("end", end.to_string()));
+        }
+
+        params.extend(self.selectors);
+
+        self.client
+            .send(
+                "api/v1/admin/tsdb/delete_series",
+                &params,
+                HttpMethod::POST,
+                None,
+            )
+            .await
+    }
+}
+
 /// Provides methods to build a query to retrieve label names from Prometheus.
 #[derive(Clone)]
 pub struct LabelNamesQueryBuilder {
     client: Client,
     selectors: Vec<(&'static str, String)>,
-    start: Option<i64>,
-    end: Option<i64>,
+    start: Option<Timestamp>,
+    end: Option<Timestamp>,
 }
 
 impl LabelNamesQueryBuilder {
@@ -489,10 +914,10 @@ impl LabelNamesQueryBuilder {
     /// reads labels from.
     /// This can be called multiple times to merge the series selectors with
     /// those that have been set before.
-    pub fn selectors<'a, T>(mut self, selectors: T) -> Self
+    pub fn selectors<T>(mut self, selectors: T) -> Self
     where
         T: IntoIterator,
-        T::Item: Borrow<Selector<'a>>,
+        T::Item: Borrow<Selector>,
     {
         self.selectors.extend(
             selectors
@@ -503,18 +928,18 @@ impl LabelNamesQueryBuilder {
     }
 
     /// Limit the amount of metadata returned by setting a start time
-    /// (UNIX timestamp in seconds).
+    /// (e.g. a Unix timestamp in seconds, see [`ToTimestamp`]).
     /// Calling this repeatedly will replace the current setting.
-    pub fn start(mut self, start: i64) -> Self {
-        self.start = Some(start);
+    pub fn start(mut self, start: impl ToTimestamp) -> Self {
+        self.start = Some(start.to_timestamp());
         self
     }
 
     /// Limit the amount of metadata returned by setting an end time
-    /// (UNIX timestamp in seconds).
+    /// (e.g. a Unix timestamp in seconds, see [`ToTimestamp`]).
     /// Calling this repeatedly will replace the current setting.
-    pub fn end(mut self, end: i64) -> Self {
-        self.end = Some(end);
+    pub fn end(mut self, end: impl ToTimestamp) -> Self {
+        self.end = Some(end.to_timestamp());
         self
     }
 
@@ -525,6 +950,13 @@ impl LabelNamesQueryBuilder {
         Client::deserialize(response).await
     }
 
+    /// Execute the query (using HTTP GET) and retrieve a collection of
+    /// label names along with any warnings and infos attached to the response.
+    pub async fn get_with_annotations(self) -> Result<(Vec<String>, Annotations), Error> {
+        let response = self.get_raw().await?;
+        Client::deserialize_annotated(response).await
+    }
+
     /// Execute the query (using HTTP GET) and retrieve the raw response.
     pub async fn get_raw(self) -> Result<reqwest::Response, Error> {
         let mut params = vec![];
@@ -552,8 +984,8 @@ pub struct LabelValuesQueryBuilder {
     client: Client,
     label: String,
     selectors: Vec<(&'static str, String)>,
-    start: Option<i64>,
-    end: Option<i64>,
+    start: Option<Timestamp>,
+    end: Option<Timestamp>,
 }
 
 impl LabelValuesQueryBuilder {
@@ -561,10 +993,10 @@ impl LabelValuesQueryBuilder {
     /// reads label values from.
     /// This can be called multiple times to merge the series selectors with
     /// those that have been set before.
-    pub fn selectors<'a, T>(mut self, selectors: T) -> Self
+    pub fn selectors<T>(mut self, selectors: T) -> Self
     where
         T: IntoIterator,
-        T::Item: Borrow<Selector<'a>>,
+        T::Item: Borrow<Selector>,
     {
         self.selectors.extend(
             selectors
@@ -575,18 +1007,18 @@ impl LabelValuesQueryBuilder {
     }
 
     /// Limit the amount of metadata returned by setting a start time
-    /// (UNIX timestamp in seconds).
+    /// (e.g. a Unix timestamp in seconds, see [`ToTimestamp`]).
     /// Calling this repeatedly will replace the current setting.
-    pub fn start(mut self, start: i64) -> Self {
-        self.start = Some(start);
+    pub fn start(mut self, start: impl ToTimestamp) -> Self {
+        self.start = Some(start.to_timestamp());
         self
     }
 
     /// Limit the amount of metadata returned by setting an end time
-    /// (UNIX timestamp in seconds).
+    /// (e.g. a Unix timestamp in seconds, see [`ToTimestamp`]).
     /// Calling this repeatedly will replace the current setting.
-    pub fn end(mut self, end: i64) -> Self {
-        self.end = Some(end);
+    pub fn end(mut self, end: impl ToTimestamp) -> Self {
+        self.end = Some(end.to_timestamp());
         self
     }
 
@@ -597,6 +1029,28 @@ impl LabelValuesQueryBuilder {
         Client::deserialize(response).await
     }
 
+    /// Execute the query (using HTTP GET) and retrieve a collection of
+    /// label values for the given label name along with any warnings and infos
+    /// attached to the response.
+    pub async fn get_with_annotations(self) -> Result<(Vec<String>, Annotations), Error> {
+        let response = self.get_raw().await?;
+        Client::deserialize_annotated(response).await
+    }
+
+    /// Execute the query (using HTTP GET) and pass each label value to the given closure
+    /// as soon as it has been parsed, i.e. without holding the entire response in memory.
+    pub async fn for_each<F>(self, mut f: F) -> Result<(), Error>
+    where
+        F: FnMut(String),
+    {
+        let response = self.get_raw().await?;
+        let mut stream = Client::stream(response, Target::Data)?;
+        while let Some(value) = stream.try_next().await? {
+            f(value);
+        }
+        Ok(())
+    }
+
     /// Execute the query (using HTTP GET) and retrieve a collection of
     /// label values for the given label name.
     pub async fn get_raw(self) -> Result<reqwest::Response, Error> {
@@ -621,10 +1075,171 @@ impl LabelValuesQueryBuilder {
 
 /// A client used to execute queries. It uses a [`reqwest::Client`] internally
 /// that manages connections for us.
+///
+/// Use [`Client::builder`] in order to configure default headers, a request timeout, a user agent
+/// or credentials that are applied to every request sent by this client.
 #[derive(Clone)]
 pub struct Client {
     pub(crate) client: reqwest::Client,
     pub(crate) base_url: Url,
+    pub(crate) headers: HeaderMap<HeaderValue>,
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) auth: Option<Auth>,
+    pub(crate) retry: Option<RetryPolicy>,
+    pub(crate) transport: Option<Arc<dyn Transport>>,
+    pub(crate) middleware: Vec<Arc<dyn Middleware>>,
+}
+
+/// A builder to create a [`Client`] with default settings that apply to all requests,
+/// e.g. headers like `X-Scope-OrgID` that are required by multi-tenant Prometheus-compatible
+/// backends.
+///
+/// ```rust
+/// use prometheus_http_query::Client;
+/// use reqwest::header::HeaderValue;
+/// use std::time::Duration;
+///
+/// fn main() -> Result<(), anyhow::Error> {
+///     let client = Client::builder()
+///         .url("https://prometheus.example.com")
+///         .header("X-Scope-OrgID", HeaderValue::from_static("tenant-1"))
+///         .timeout(Duration::from_secs(30))
+///         .user_agent(HeaderValue::from_static("my-app/1.0"))
+///         .basic_auth("user", Some("secret"))
+///         .build()?;
+///
+///     assert_eq!(client.base_url().as_str(), "https://prometheus.example.com/");
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct ClientBuilder {
+    client: Option<reqwest::Client>,
+    url: Option<String>,
+    headers: HeaderMap<HeaderValue>,
+    timeout: Option<Duration>,
+    auth: Option<Auth>,
+    retry: Option<RetryPolicy>,
+    transport: Option<Arc<dyn Transport>>,
+    middleware: Vec<Arc<dyn Middleware>>,
+}
+
+impl ClientBuilder {
+    /// Set the base URL of the Prometheus server. Note that the API-specific
+    /// path segments (like `/api/v1/query`) are added automatically.
+    /// If this is not set the client sends requests to "http://127.0.0.1:9090/".
+    pub fn url(mut self, url: impl Into<String>) -> Self {
+        self.url = Some(url.into());
+        self
+    }
+
+    /// Use a custom [`reqwest::Client`], e.g. to account for x509 authentication.
+    pub fn client(mut self, client: reqwest::Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Include an additional header in every request. Headers that are set on individual
+    /// requests (e.g. via [`InstantQueryBuilder::header`]) take precedence.
+    pub fn header<K: IntoHeaderName, T: Into<HeaderValue>>(mut self, name: K, value: T) -> Self {
+        self.headers.append(name, value.into());
+        self
+    }
+
+    /// Set a timeout for every request, from when the request starts connecting until
+    /// the response body has finished.
+    /// Calling this repeatedly will replace the current timeout.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Set the `User-Agent` header that is included in every request.
+    /// Calling this repeatedly will replace the current user agent.
+    pub fn user_agent<T: Into<HeaderValue>>(mut self, value: T) -> Self {
+        self.headers.insert(USER_AGENT, value.into());
+        self
+    }
+
+    /// Authenticate every request using HTTP basic authentication.
+    /// Calling this repeatedly will replace the current credentials.
+    pub fn basic_auth(
+        mut self,
+        username: impl std::fmt::Display,
+        password: Option<impl std::fmt::Display>,
+    ) -> Self {
+        self.auth = Some(Auth::Basic {
+            username: username.to_string(),
+            password: password.map(|p| p.to_string()),
+        });
+        self
+    }
+
+    /// Authenticate every request using a bearer token.
+    /// Calling this repeatedly will replace the current credentials.
+    pub fn bearer_auth(mut self, token: impl std::fmt::Display) -> Self {
+        self.auth = Some(Auth::Bearer(token.to_string()));
+        self
+    }
+
+    /// Authenticate every request using a bearer token that is read from the given file.
+    /// The file is re-read for every request in order to pick up rotated tokens, like
+    /// Prometheus does with `credentials_file`.
+    /// Calling this repeatedly will replace the current credentials.
+    pub fn bearer_auth_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.auth = Some(Auth::BearerFile(path.into()));
+        self
+    }
+
+    /// Authenticate every request using the given credentials.
+    /// Calling this repeatedly will replace the current credentials.
+    pub fn auth(mut self, auth: Auth) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    /// Automatically retry requests that failed due to transient errors according
+    /// to the given policy.
+    /// Requests that modify data on the server, i.e. the admin API, are never retried.
+    /// Calling this repeatedly will replace the current policy.
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    /// Send requests using a custom [`Transport`] instead of the [`reqwest::Client`].
+    /// Calling this repeatedly will replace the current transport.
+    pub fn transport(mut self, transport: impl Transport + 'static) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Add a [`Middleware`] that is invoked for every API request.
+    /// This method can be called repeatedly to add more middleware, which is invoked
+    /// in the order it was added.
+    pub fn middleware(mut self, middleware: impl Middleware + 'static) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Create the [`Client`]. Fails if the base URL is invalid.
+    pub fn build(self) -> Result<Client, Error> {
+        let base_url = match self.url {
+            Some(url) => url.to_base_url()?,
+            None => Url::parse("http://127.0.0.1:9090/").unwrap(),
+        };
+
+        Ok(Client {
+            client: self.client.unwrap_or_default(),
+            base_url,
+            headers: self.headers,
+            timeout: self.timeout,
+            auth: self.auth,
+            retry: self.retry,
+            transport: self.transport,
+            middleware: self.middleware,
+        })
+    }
 }
 
 impl Default for Client {
@@ -636,10 +1251,7 @@ impl Default for Client {
     /// let client = Client::default();
     /// ```
     fn default() -> Self {
-        Client {
-            client: reqwest::Client::new(),
-            base_url: Url::parse("http://127.0.0.1:9090/").unwrap(),
-        }
+        ClientBuilder::default().build().unwrap()
     }
 }
 
@@ -657,11 +1269,7 @@ impl std::str::FromStr for Client {
     /// assert!(client.is_ok());
     /// ```
     fn from_str(url: &str) -> Result<Self, Self::Err> {
-        let client = Client {
-            base_url: url.to_base_url()?,
-            client: reqwest::Client::new(),
-        };
-        Ok(client)
+        Client::builder().url(url).build()
     }
 }
 
@@ -679,11 +1287,7 @@ impl std::convert::TryFrom<&str> for Client {
     /// assert!(client.is_ok());
     /// ```
     fn try_from(url: &str) -> Result<Self, Self::Error> {
-        let client = Client {
-            base_url: url.to_base_url()?,
-            client: reqwest::Client::new(),
-        };
-        Ok(client)
+        Client::builder().url(url).build()
     }
 }
 
@@ -702,11 +1306,7 @@ impl std::convert::TryFrom<String> for Client {
     /// assert!(client.is_ok());
     /// ```
     fn try_from(url: String) -> Result<Self, Self::Error> {
-        let client = Client {
-            base_url: url.to_base_url()?,
-            client: reqwest::Client::new(),
-        };
-        Ok(client)
+        Client::builder().url(url).build()
     }
 }
 
@@ -714,6 +1314,9 @@ impl Client {
     /// Return a reference to the wrapped [`reqwest::Client`], i.e. to
     /// use it for other requests unrelated to the Prometheus API.
     ///
+    /// Note that this exposes the HTTP client that is used internally, so its type
+    /// may change whenever the `reqwest` dependency is upgraded.
+    ///
     /// ```rust
     /// use prometheus_http_query::{Client};
     ///
@@ -723,7 +1326,7 @@ impl Client {
     ///
     ///     // An amittedly bad example, but that is not the point.
     ///     let response = client
-    ///         .inner()
+    ///         .reqwest_client()
     ///         .head("http://127.0.0.1:9090")
     ///         .send()
     ///         .await?;
@@ -733,7 +1336,7 @@ impl Client {
     ///     Ok(())
     /// }
     /// ```
-    pub fn inner(&self) -> &reqwest::Client {
+    pub fn reqwest_client(&self) -> &reqwest::Client {
         &self.client
     }
 
@@ -777,39 +1380,207 @@ impl Client {
     /// }
     /// ```
     pub fn from(client: reqwest::Client, url: &str) -> Result<Self, Error> {
-        let base_url = url.to_base_url()?;
-        Ok(Client { base_url, client })
+        Client::builder().client(client).url(url).build()
     }
 
-    /// Build and send the final HTTP request. Parse the result as JSON if the
-    /// `Content-Type` header indicates that the payload is JSON. Otherwise it is
-    /// assumed that an intermediate proxy sends a plain text error.
-    async fn send<S: Serialize>(
-        &self,
-        path: &str,
+    /// Create a [`ClientBuilder`] to configure settings that apply to all requests
+    /// sent by the [`Client`].
+    ///
+    /// ```rust
+    /// use prometheus_http_query::Client;
+    ///
+    /// let client = Client::builder()
+    ///     .url("http://proxy.example.com/prometheus")
+    ///     .bearer_auth("token")
+    ///     .build();
+    ///
+    /// assert!(client.is_ok());
+    /// ```
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::default()
+    }
+
+    // Create a request to the given URL and apply the default settings of this client.
+    async fn request(
+        &self,
+        method: HttpMethod,
+        url: Url,
+    ) -> Result<reqwest::RequestBuilder, Error> {
+        let mut request = self
+            .client
+            .request(method, url)
+            .headers(self.headers.clone());
+
+        if let Some(timeout) = self.timeout {
+            request = request.timeout(timeout);
+        }
+
+        match &self.auth {
+            Some(auth) => auth.apply(request, &self.client).await,
+            None => Ok(request),
+        }
+    }
+
+    // Create a request with the default settings of this client, customize, sign and send it.
+    // If the server rejects cached credentials (HTTP 401), these are refreshed and the
+    // request is sent once more. Transient failures of idempotent requests are retried
+    // according to the retry policy.
+    async fn execute<F>(
+        &self,
+        method: HttpMethod,
+        url: Url,
+        idempotent: bool,
+        message: &'static str,
+        customize: F,
+    ) -> Result<reqwest::Response, Error>
+    where
+        F: Fn(reqwest::RequestBuilder) -> reqwest::RequestBuilder,
+    {
+        let retry = self.retry.as_ref().filter(|_| idempotent);
+        let mut attempt = 1;
+        let mut refreshed = false;
+
+        loop {
+            let request = self.request(method.clone(), url.clone()).await?;
+
+            let mut request = customize(request).build().map_err(|source| {
+                Error::Client(ClientError {
+                    message: "failed to build request",
+                    source: Some(source.into()),
+                })
+            })?;
+
+            if let Some(auth) = &self.auth {
+                auth.sign(&mut request);
+            }
+
+            let authorization = request.headers().get(AUTHORIZATION).cloned();
+
+            // The response of the `reqwest::Client` is passed through as is instead of being
+            // buffered like the responses of custom transports, so that its body can be streamed.
+            let result = match &self.transport {
+                Some(transport) => transport
+                    .send(HttpRequest::from_request(request))
+                    .await
+                    .map(HttpResponse::into_response),
+                None => self.client.execute(request).await.map_err(Into::into),
+            };
+
+            let delay = match result {
+                Ok(response) => {
+                    if response.status() == StatusCode::UNAUTHORIZED && !refreshed {
+                        if let Some(auth) = &self.auth {
+                            if auth.invalidate(authorization.as_ref()).await {
+                                refreshed = true;
+                                continue;
+                            }
+                        }
+                    }
+
+                    match retry.and_then(|p| p.retry_response(attempt, &response)) {
+                        Some(delay) => delay,
+                        None => return Ok(response),
+                    }
+                }
+                Err(source) => match retry.and_then(|p| p.retry_error(attempt, source.as_ref())) {
+                    Some(delay) => delay,
+                    None => return Err(Error::Transport(TransportError { message, source })),
+                },
+            };
+
+            attempt += 1;
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Build and send the final HTTP request. Parse the result as JSON if the
+    /// `Content-Type` header indicates that the payload is JSON. Otherwise it is
+    /// assumed that an intermediate proxy sends a plain text error.
+    async fn send<S: Serialize>(
+        &self,
+        path: &str,
         params: &S,
         method: HttpMethod,
         headers: Option<HeaderMap<HeaderValue>>,
     ) -> Result<reqwest::Response, Error> {
-        let url = build_final_url(self.base_url.clone(), path);
+        let params = serde_urlencoded::to_string(params).map_err(|_| {
+            Error::Client(ClientError {
+                message: "failed to serialize request parameters",
+                source: None,
+            })
+        })?;
 
-        let mut request = match method {
-            HttpMethod::GET => self.client.get(url).query(params),
-            HttpMethod::POST => self.client.post(url).form(params),
-            _ => unreachable!(),
+        let request = MiddlewareRequest {
+            method,
+            path: path.to_string(),
+            params: url::form_urlencoded::parse(params.as_bytes())
+                .into_owned()
+                .collect(),
+            headers: headers.unwrap_or_default(),
         };
 
-        if let Some(headers) = headers {
-            request = request.headers(headers);
+        self.dispatch(request, "failed to send request to server")
+            .await
+    }
+
+    // Pass the request through the middleware chain and execute it unless a middleware
+    // responds in place of the server.
+    async fn dispatch(
+        &self,
+        mut request: MiddlewareRequest,
+        message: &'static str,
+    ) -> Result<reqwest::Response, Error> {
+        let started = Instant::now();
+        let mut called = 0;
+        let mut short_circuit = None;
+
+        for middleware in &self.middleware {
+            called += 1;
+            if let Some(response) = middleware.before(&mut request) {
+                short_circuit = Some(response);
+                break;
+            }
         }
 
-        let response = request.send().await.map_err(|source| {
-            Error::Client(ClientError {
-                message: "failed to send request to server",
-                source: Some(source),
-            })
-        })?;
-        Ok(response)
+        let result = match short_circuit {
+            Some(response) => Ok(response.into_response()),
+            None => {
+                let url = build_final_url(self.base_url.clone(), &request.path);
+                let is_post = request.method == HttpMethod::POST;
+                // POST requests are only safe to repeat if they are sent to read-only endpoints.
+                let idempotent = !is_post || READ_ONLY_ENDPOINTS.contains(&request.path.as_str());
+
+                self.execute(
+                    request.method.clone(),
+                    url,
+                    idempotent,
+                    message,
+                    |builder| {
+                        let builder = if is_post {
+                            builder.form(&request.params)
+                        } else {
+                            builder.query(&request.params)
+                        };
+                        builder.headers(request.headers.clone())
+                    },
+                )
+                .await
+            }
+        };
+
+        if called > 0 {
+            let response = result.as_ref().map(|r| MiddlewareResponse {
+                status: r.status(),
+                headers: r.headers().clone(),
+                elapsed: started.elapsed(),
+            });
+
+            for middleware in self.middleware[..called].iter().rev() {
+                middleware.after(&request, response.as_ref().map_err(|e| *e));
+            }
+        }
+
+        result
     }
 
     /// Create an [`InstantQueryBuilder`] from a PromQL query allowing you to set some query parameters
@@ -852,9 +1623,9 @@ impl Client {
     ///
     /// # Arguments
     /// * `query` - PromQL query to exeute
-    /// * `start` - Start timestamp as Unix timestamp (seconds)
-    /// * `end` - End timestamp as Unix timestamp (seconds)
-    /// * `step` - Query resolution step width as float number of seconds
+    /// * `start` - Start timestamp, e.g. as Unix timestamp in seconds (see [`ToTimestamp`])
+    /// * `end` - End timestamp, e.g. as Unix timestamp in seconds (see [`ToTimestamp`])
+    /// * `step` - Query resolution step width, e.g. as float number of seconds or duration string like `"30s"` (see [`ToQueryDuration`])
     ///
     /// See also: [Prometheus API documentation](https://prometheus.io/docs/prometheus/latest/querying/api/#range-queries)
     ///
@@ -882,10 +1653,15 @@ impl Client {
     pub fn query_range(
         &self,
         query: impl std::fmt::Display,
-        start: i64,
-        end: i64,
-        step: f64,
+        start: impl ToTimestamp,
+        end: impl ToTimestamp,
+        step: impl ToQueryDuration,
     ) -> RangeQueryBuilder {
+        let (start, end, step) = (
+            start.to_timestamp(),
+            end.to_timestamp(),
+            step.to_query_duration(),
+        );
         RangeQueryBuilder {
             client: self.clone(),
             params: vec![
@@ -895,9 +1671,126 @@ impl Client {
                 ("step", step.to_string()),
             ],
             headers: Default::default(),
+            range: (start, end, step),
+            split: None,
+            split_concurrency: 4,
+        }
+    }
+
+    /// Create an [`ExemplarsQueryBuilder`] from a PromQL query allowing you to set some query parameters
+    /// before finally sending the exemplar query to the server.
+    ///
+    /// # Arguments
+    /// * `query` - PromQL query whose series' exemplars are returned
+    /// * `start` - Start timestamp, e.g. as Unix timestamp in seconds (see [`ToTimestamp`])
+    /// * `end` - End timestamp, e.g. as Unix timestamp in seconds (see [`ToTimestamp`])
+    ///
+    /// See also: [Prometheus API documentation](https://prometheus.io/docs/prometheus/latest/querying/api/#querying-exemplars)
+    ///
+    /// ```rust
+    /// use prometheus_http_query::Client;
+    ///
+    /// #[tokio::main(flavor = "current_thread")]
+    /// async fn main() -> Result<(), anyhow::Error> {
+    ///     let client = Client::default();
+    ///
+    ///     let q = "prometheus_http_request_duration_seconds_bucket";
+    ///
+    ///     let response = client.query_exemplars(q, 1648373100, 1648373300).get().await?;
+    ///
+    ///     for series in response {
+    ///         for exemplar in series.exemplars() {
+    ///             println!("{:?} {}", exemplar.labels().get("trace_id"), exemplar.value());
+    ///         }
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn query_exemplars(
+        &self,
+        query: impl std::fmt::Display,
+        start: impl ToTimestamp,
+        end: impl ToTimestamp,
+    ) -> ExemplarsQueryBuilder {
+        ExemplarsQueryBuilder {
+            client: self.clone(),
+            params: vec![
+                ("query", query.to_string()),
+                ("start", start.to_timestamp().to_string()),
+                ("end", end.to_timestamp().to_string()),
+            ],
+            headers: Default::default(),
         }
     }
 
+    /// Format a PromQL query the way Prometheus would pretty-print it.
+    ///
+    /// If the query cannot be parsed an [`Error::Prometheus`] is returned and
+    /// [`PrometheusError::position`](crate::error::PrometheusError::position) reports
+    /// where parsing failed.
+    ///
+    /// See also: [Prometheus API documentation](https://prometheus.io/docs/prometheus/latest/querying/api/#formatting-query-expressions)
+    ///
+    /// ```rust
+    /// use prometheus_http_query::Client;
+    ///
+    /// #[tokio::main(flavor = "current_thread")]
+    /// async fn main() -> Result<(), anyhow::Error> {
+    ///     let client = Client::default();
+    ///
+    ///     let response = client.format_query("foo/bar").await?;
+    ///
+    ///     assert_eq!(response, "foo / bar");
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn format_query(&self, query: impl std::fmt::Display) -> Result<String, Error> {
+        let params = [("query", query.to_string())];
+        let response = self
+            .send("api/v1/format_query", &params, HttpMethod::GET, None)
+            .await?;
+        Client::deserialize(response).await
+    }
+
+    /// Parse a PromQL query and return its abstract syntax tree.
+    ///
+    /// If the query cannot be parsed an [`Error::Prometheus`] is returned and
+    /// [`PrometheusError::position`](crate::error::PrometheusError::position) reports
+    /// where parsing failed.
+    ///
+    /// Note that this endpoint is considered experimental by Prometheus and may change.
+    ///
+    /// ```rust
+    /// use prometheus_http_query::Client;
+    ///
+    /// #[tokio::main(flavor = "current_thread")]
+    /// async fn main() -> Result<(), anyhow::Error> {
+    ///     let client = Client::default();
+    ///
+    ///     let node = client.parse_query("sum by (job) (up)").await?;
+    ///
+    ///     assert!(node.as_aggregation().is_some_and(|a| a.op() == "sum"));
+    ///
+    ///     let error = client.parse_query("sum by (job) (up").await.unwrap_err();
+    ///
+    ///     match error {
+    ///         prometheus_http_query::Error::Prometheus(e) => assert!(e.position().is_some()),
+    ///         _ => panic!("expected a parse error"),
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn parse_query(&self, query: impl std::fmt::Display) -> Result<Node, Error> {
+        let params = [("query", query.to_string())];
+        let response = self
+            .send("api/v1/parse_query", &params, HttpMethod::GET, None)
+            .await?;
+        Client::deserialize(response).await
+    }
+
     /// Create a [`SeriesQueryBuilder`] to apply filters to a series metadata
     /// query before sending it to Prometheus.
     ///
@@ -927,10 +1820,10 @@ impl Client {
     ///     Ok(())
     /// }
     /// ```
-    pub fn series<'a, T>(&self, selectors: T) -> Result<SeriesQueryBuilder, Error>
+    pub fn series<T>(&self, selectors: T) -> Result<SeriesQueryBuilder, Error>
     where
         T: IntoIterator,
-        T::Item: Borrow<Selector<'a>>,
+        T::Item: Borrow<Selector>,
     {
         let selectors: Vec<(&str, String)> = selectors
             .into_iter()
@@ -1203,6 +2096,35 @@ impl Client {
         Client::deserialize(response).await
     }
 
+    /// Retrieve the configuration that the Prometheus server is currently running with.
+    ///
+    /// See also: [Prometheus API documentation](https://prometheus.io/docs/prometheus/latest/querying/api/#config)
+    ///
+    /// ```rust
+    /// use prometheus_http_query::Client;
+    ///
+    /// #[tokio::main(flavor = "current_thread")]
+    /// async fn main() -> Result<(), anyhow::Error> {
+    ///     let client = Client::default();
+    ///
+    ///     let config = client.config().await?;
+    ///
+    ///     if let Some(parsed) = config.parsed() {
+    ///         for scrape_config in parsed.scrape_configs() {
+    ///             println!("{}: {:?}", scrape_config.job_name(), scrape_config.scrape_interval());
+    ///         }
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn config(&self) -> Result<Config, Error> {
+        let response = self
+            .send("api/v1/status/config", &(), HttpMethod::GET, None)
+            .await?;
+        Client::deserialize(response).await
+    }
+
     /// Retrieve Prometheus TSDB statistics.
     ///
     /// See also: [Prometheus API documentation](https://prometheus.io/docs/prometheus/latest/querying/api/#tsdb-stats)
@@ -1316,7 +2238,7 @@ impl Client {
     ///     Ok(())
     /// }
     /// ```
-    pub fn target_metadata<'a>(&self) -> TargetMetadataQueryBuilder<'a> {
+    pub fn target_metadata(&self) -> TargetMetadataQueryBuilder {
         TargetMetadataQueryBuilder {
             client: self.clone(),
             match_target: None,
@@ -1369,6 +2291,132 @@ impl Client {
         }
     }
 
+    /// Create a snapshot of all current data in the Prometheus TSDB.
+    /// The snapshot is stored in the `snapshots` directory below the TSDB data directory.
+    ///
+    /// Note that the admin API must be enabled on the Prometheus server using the
+    /// `--web.enable-admin-api` flag.
+    ///
+    /// # Arguments
+    /// * `skip_head` - Skip data present in the head block that has not yet been compacted to disk
+    ///
+    /// See also: [Prometheus API documentation](https://prometheus.io/docs/prometheus/latest/querying/api/#snapshot)
+    ///
+    /// ```rust,no_run
+    /// use prometheus_http_query::Client;
+    ///
+    /// #[tokio::main(flavor = "current_thread")]
+    /// async fn main() -> Result<(), anyhow::Error> {
+    ///     let client = Client::default();
+    ///
+    ///     let snapshot = client.snapshot(false).await?;
+    ///
+    ///     println!("Created snapshot {}", snapshot.name());
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn snapshot(&self, skip_head: bool) -> Result<Snapshot, Error> {
+        let params = [("skip_head", skip_head.to_string())];
+
+        let response = self
+            .send(
+                "api/v1/admin/tsdb/snapshot",
+                &params,
+                HttpMethod::POST,
+                None,
+            )
+            .await?;
+        Client::deserialize(response).await
+    }
+
+    /// Create a [`DeleteSeriesQueryBuilder`] to delete data for a selection of series
+    /// in a time range. The data is not removed from disk immediately but marked
+    /// for deletion until the next compaction (see [`Client::clean_tombstones`]).
+    ///
+    /// Note that the admin API must be enabled on the Prometheus server using the
+    /// `--web.enable-admin-api` flag.
+    ///
+    /// # Arguments
+    /// * `selectors` - Iterable container of [`Selector`]s that tells Prometheus which series to delete. Must not be empty!
+    ///
+    /// See also: [Prometheus API documentation](https://prometheus.io/docs/prometheus/latest/querying/api/#delete-series)
+    ///
+    /// ```rust,no_run
+    /// use prometheus_http_query::{Client, Selector};
+    ///
+    /// #[tokio::main(flavor = "current_thread")]
+    /// async fn main() -> Result<(), anyhow::Error> {
+    ///     let client = Client::default();
+    ///
+    ///     let s = Selector::new()
+    ///         .eq("job", "node")
+    ///         .eq("instance", "decommissioned.example.com:9100");
+    ///
+    ///     client
+    ///         .delete_series(&[s])?
+    ///         .start(1648373100)
+    ///         .end(1648373300)
+    ///         .post()
+    ///         .await?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn delete_series<T>(&self, selectors: T) -> Result<DeleteSeriesQueryBuilder, Error>
+    where
+        T: IntoIterator,
+        T::Item: Borrow<Selector>,
+    {
+        let selectors: Vec<(&str, String)> = selectors
+            .into_iter()
+            .map(|s| ("match[]", s.borrow().to_string()))
+            .collect();
+
+        if selectors.is_empty() {
+            Err(Error::EmptySeriesSelector)
+        } else {
+            Ok(DeleteSeriesQueryBuilder {
+                client: self.clone(),
+                selectors,
+                start: None,
+                end: None,
+            })
+        }
+    }
+
+    /// Remove data that was deleted using [`Client::delete_series`] from disk and clean up
+    /// the existing tombstones.
+    ///
+    /// Note that the admin API must be enabled on the Prometheus server using the
+    /// `--web.enable-admin-api` flag.
+    ///
+    /// See also: [Prometheus API documentation](https://prometheus.io/docs/prometheus/latest/querying/api/#clean-tombstones)
+    ///
+    /// ```rust,no_run
+    /// use prometheus_http_query::Client;
+    ///
+    /// #[tokio::main(flavor = "current_thread")]
+    /// async fn main() -> Result<(), anyhow::Error> {
+    ///     let client = Client::default();
+    ///
+    ///     client.clean_tombstones().await?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn clean_tombstones(&self) -> Result<(), Error> {
+        let response = self
+            .send(
+                "api/v1/admin/tsdb/clean_tombstones",
+                &(),
+                HttpMethod::POST,
+                None,
+            )
+            .await?;
+        Client::expect_no_content(response).await
+    }
+
     /// Check Prometheus server health.
     ///
     /// See also: [Prometheus API documentation](https://prometheus.io/docs/prometheus/latest/management_api/#health-check)
@@ -1384,22 +2432,20 @@ impl Client {
     /// }
     /// ```
     pub async fn is_server_healthy(&self) -> Result<bool, Error> {
-        let url = build_final_url(self.base_url.clone(), "-/healthy");
-        self.client
-            .get(url)
-            .send()
-            .await
-            .map_err(|source| {
-                Error::Client(ClientError {
-                    message: "failed to send request to health endpoint",
-                    source: Some(source),
-                })
-            })?
+        let request = MiddlewareRequest {
+            method: HttpMethod::GET,
+            path: String::from("-/healthy"),
+            params: vec![],
+            headers: HeaderMap::new(),
+        };
+
+        self.dispatch(request, "failed to send request to health endpoint")
+            .await?
             .error_for_status()
             .map_err(|source| {
                 Error::Client(ClientError {
                     message: "request to health endpoint returned an error",
-                    source: Some(source),
+                    source: Some(source.into()),
                 })
             })
             .map(|_| true)
@@ -1420,22 +2466,20 @@ impl Client {
     /// }
     /// ```
     pub async fn is_server_ready(&self) -> Result<bool, Error> {
-        let url = build_final_url(self.base_url.clone(), "-/ready");
-        self.client
-            .get(url)
-            .send()
-            .await
-            .map_err(|source| {
-                Error::Client(ClientError {
-                    message: "failed to send request to readiness endpoint",
-                    source: Some(source),
-                })
-            })?
+        let request = MiddlewareRequest {
+            method: HttpMethod::GET,
+            path: String::from("-/ready"),
+            params: vec![],
+            headers: HeaderMap::new(),
+        };
+
+        self.dispatch(request, "failed to send request to readiness endpoint")
+            .await?
             .error_for_status()
             .map_err(|source| {
                 Error::Client(ClientError {
                     message: "request to readiness endpoint returned an error",
-                    source: Some(source),
+                    source: Some(source.into()),
                 })
             })
             .map(|_| true)
@@ -1446,22 +2490,597 @@ impl Client {
     // Internally, the response is deserialized into the [`ApiResponse`] type first.
     // On success, the data is returned as is. On failure, the error is mapped to the appropriate [`Error`] type.
     async fn deserialize<D: DeserializeOwned>(response: reqwest::Response) -> Result<D, Error> {
-        let header = CONTENT_TYPE;
-        if !util::is_json(response.headers().get(header)) {
+        Client::deserialize_annotated(response)
+            .await
+            .map(|(data, _)| data)
+    }
+
+    // Same as [`Client::deserialize`] but also returns the warnings and infos that
+    // Prometheus attached to a successful response.
+    async fn deserialize_annotated<D: DeserializeOwned>(
+        response: reqwest::Response,
+    ) -> Result<(D, Annotations), Error> {
+        let response = Client::expect_json(response)?
+            .json::<ApiResponse<D>>()
+            .await
+            .map_err(|source| {
+                Error::Client(ClientError {
+                    message: "failed to parse JSON response from server",
+                    source: Some(source.into()),
+                })
+            })?;
+        match response {
+            ApiResponse::Success {
+                data,
+                warnings,
+                infos,
+            } => Ok((data, Annotations { warnings, infos })),
+            ApiResponse::Error(e) => Err(Error::Prometheus(e)),
+        }
+    }
+
+    // Parse the elements of the target array of the response one at a time. The response
+    // is checked like in [`Client::deserialize`] first.
+    fn stream<T>(response: reqwest::Response, target: Target) -> Result<SeriesStream<T>, Error> {
+        Client::expect_json(response).map(|response| SeriesStream::new(response, target))
+    }
+
+    fn expect_json(response: reqwest::Response) -> Result<reqwest::Response, Error> {
+        if !util::is_json(response.headers().get(CONTENT_TYPE)) {
             return Err(Error::Client(ClientError {
                 message: "failed to parse response from server due to invalid media type",
-                source: response.error_for_status().err(),
+                source: response.error_for_status().err().map(Into::into),
             }));
         }
-        let response = response.json::<ApiResponse<D>>().await.map_err(|source| {
-            Error::Client(ClientError {
-                message: "failed to parse JSON response from server",
-                source: Some(source),
+        Ok(response)
+    }
+
+    // Check the response of an endpoint that returns HTTP 204 on success.
+    //
+    // Error responses are handled like in [`Client::deserialize`], i.e. a JSON body is mapped
+    // to the appropriate [`Error`] type.
+    async fn expect_no_content(response: reqwest::Response) -> Result<(), Error> {
+        if response.status() == StatusCode::NO_CONTENT {
+            return Ok(());
+        }
+        Client::deserialize::<IgnoredAny>(response)
+            .await
+            .map(|_| ())
+    }
+
+    // Deserialize the response of an instant or range query and attach the
+    // warnings and infos to the [`PromqlResult`].
+    async fn deserialize_promql(response: reqwest::Response) -> Result<PromqlResult, Error> {
+        Client::deserialize_annotated(response).await.map(
+            |(mut result, annotations): (PromqlResult, Annotations)| {
+                result.annotations = annotations;
+                result
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::OAuth2;
+    use crate::test_util::{json_response, raw_response, serve, StaticTransport};
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_client_builder_defaults() -> Result<(), anyhow::Error> {
+        let client = Client::builder()
+            .url("http://prometheus.example.com")
+            .header("X-Scope-OrgID", HeaderValue::from_static("tenant-1"))
+            .user_agent(HeaderValue::from_static("test/1.0"))
+            .timeout(Duration::from_secs(5))
+            .bearer_auth("token")
+            .build()?;
+
+        let url = build_final_url(client.base_url.clone(), "-/ready");
+        let request = client.request(HttpMethod::GET, url).await?.build()?;
+
+        assert_eq!(
+            request.url().as_str(),
+            "http://prometheus.example.com/-/ready"
+        );
+        assert_eq!(request.headers()["X-Scope-OrgID"], "tenant-1");
+        assert_eq!(request.headers()[USER_AGENT], "test/1.0");
+        assert_eq!(request.headers()[AUTHORIZATION], "Bearer token");
+        assert_eq!(request.timeout(), Some(&Duration::from_secs(5)));
+
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Scope-OrgID", HeaderValue::from_static("tenant-2"));
+        let request = client
+            .request(HttpMethod::GET, client.base_url.clone())
+            .await?
+            .headers(headers)
+            .build()?;
+
+        assert_eq!(request.headers()["X-Scope-OrgID"], "tenant-2");
+
+        let client = Client::builder().basic_auth("user", Some("pass")).build()?;
+        let request = client
+            .request(HttpMethod::GET, client.base_url.clone())
+            .await?
+            .build()?;
+
+        assert_eq!(request.headers()[AUTHORIZATION], "Basic dXNlcjpwYXNz");
+        assert_eq!(client.base_url.as_str(), "http://127.0.0.1:9090/");
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_bearer_auth_file() -> Result<(), anyhow::Error> {
+        let path = std::env::temp_dir().join(format!("prometheus-token-{}", std::process::id()));
+        let client = Client::builder().bearer_auth_file(&path).build()?;
+
+        assert!(matches!(
+            client
+                .request(HttpMethod::GET, client.base_url.clone())
+                .await,
+            Err(Error::Auth(_))
+        ));
+
+        std::fs::write(&path, "first\n")?;
+        let request = client
+            .request(HttpMethod::GET, client.base_url.clone())
+            .await?
+            .build()?;
+        assert_eq!(request.headers()[AUTHORIZATION], "Bearer first");
+
+        std::fs::write(&path, "second")?;
+        let request = client
+            .request(HttpMethod::GET, client.base_url.clone())
+            .await?
+            .build()?;
+        assert_eq!(request.headers()[AUTHORIZATION], "Bearer second");
+
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_oauth2_refresh_on_unauthorized() -> Result<(), anyhow::Error> {
+        let token = |t: &str| {
+            let body = format!(r#"{{"access_token":"{}","expires_in":3600}}"#, t);
+            raw_response("200 OK", &body)
+        };
+
+        let (url, handle) = serve(vec![
+            token("first"),
+            raw_response("401 Unauthorized", ""),
+            token("second"),
+            raw_response("200 OK", ""),
+        ]);
+
+        let oauth2 = OAuth2::new("id", "secret", &format!("{}/token", url))?;
+        let client = Client::builder()
+            .url(&url)
+            .auth(Auth::OAuth2(oauth2))
+            .build()?;
+
+        assert!(client.is_server_ready().await?);
+
+        let requests = handle.join().unwrap();
+        assert!(requests[0].starts_with("post /token"));
+        assert!(requests[0].contains("grant_type=client_credentials"));
+        assert!(requests[1].contains("authorization: bearer first"));
+        assert!(requests[2].starts_with("post /token"));
+        assert!(requests[3].contains("authorization: bearer second"));
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_oauth2_single_token_request() -> Result<(), anyhow::Error> {
+        // A lifetime that exceeds the range of `Instant` is treated as no expiry.
+        let body = r#"{"access_token":"token","expires_in":18446744073709551615}"#;
+        let token = raw_response("200 OK", body);
+        let ok = || raw_response("200 OK", "");
+
+        let (url, handle) = serve(vec![token, ok(), ok()]);
+
+        let oauth2 = OAuth2::new("id", "secret", &format!("{}/token", url))?;
+        let client = Client::builder()
+            .url(&url)
+            .auth(Auth::OAuth2(oauth2))
+            .build()?;
+
+        let (a, b) = tokio::join!(client.is_server_ready(), client.is_server_ready());
+        assert!(a? && b?);
+
+        let requests = handle.join().unwrap();
+        assert!(requests[0].starts_with("post /token"));
+        assert!(requests[1..]
+            .iter()
+            .all(|r| r.contains("authorization: bearer token")));
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_retry_transient_failures() -> Result<(), anyhow::Error> {
+        let unavailable = || {
+            String::from(
+                "HTTP/1.1 503 Service Unavailable\r\nRetry-After: 0\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            )
+        };
+        let ok = || raw_response("200 OK", "");
+
+        let (url, handle) = serve(vec![unavailable(), unavailable(), ok()]);
+        let policy = RetryPolicy::new().initial_backoff(Duration::from_millis(1));
+        let client = Client::builder().url(&url).retry(policy).build()?;
+
+        assert!(client.is_server_ready().await?);
+        assert_eq!(handle.join().unwrap().len(), 3);
+
+        let (url, handle) = serve(vec![unavailable(), unavailable()]);
+        let policy = RetryPolicy::new()
+            .max_attempts(2)
+            .initial_backoff(Duration::from_millis(1));
+        let client = Client::builder().url(&url).retry(policy).build()?;
+
+        assert!(client.is_server_ready().await.is_err());
+        assert_eq!(handle.join().unwrap().len(), 2);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_retry_closed_connections() -> Result<(), anyhow::Error> {
+        // An empty response closes the connection before the server responded.
+        let closed = String::new;
+        let ok = || raw_response("200 OK", "");
+
+        let (url, handle) = serve(vec![closed(), ok()]);
+        let policy = RetryPolicy::new().initial_backoff(Duration::from_millis(1));
+        let client = Client::builder().url(&url).retry(policy).build()?;
+
+        assert!(client.is_server_ready().await?);
+        assert_eq!(handle.join().unwrap().len(), 2);
+
+        let (url, handle) = serve(vec![closed()]);
+        let client = Client::try_from(url)?;
+
+        let error = client.is_server_ready().await.unwrap_err();
+        assert!(crate::retry::is_connect(&error));
+        handle.join().unwrap();
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_retry_skips_admin_requests() -> Result<(), anyhow::Error> {
+        let (url, handle) = serve(vec![
+            String::from(
+                "HTTP/1.1 503 Service Unavailable\r\nRetry-After: 0\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            ),
+            raw_response("200 OK", ""),
+        ]);
+        let policy = RetryPolicy::new().initial_backoff(Duration::from_millis(1));
+        let client = Client::builder().url(&url).retry(policy).build()?;
+
+        // The second response would be consumed by a retry of the snapshot request.
+        assert!(client.snapshot(false).await.is_err());
+        assert!(client.is_server_ready().await?);
+
+        let requests = handle.join().unwrap();
+        assert!(requests[0].starts_with("post /api/v1/admin/tsdb/snapshot"));
+        assert!(requests[1].starts_with("get /-/ready"));
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_custom_transport() -> Result<(), anyhow::Error> {
+        use std::sync::Mutex;
+
+        let requests = Arc::new(Mutex::new(vec![]));
+        let transport = StaticTransport::new({
+            let requests = Arc::clone(&requests);
+            move |request: &HttpRequest| {
+                requests.lock().unwrap().push(request.clone());
+                Ok(json_response(r#"{"status":"success","data":["up"]}"#))
+            }
+        });
+
+        let client = Client::builder()
+            .url("http://prometheus.example.com")
+            .bearer_auth("token")
+            .transport(transport)
+            .build()?;
+
+        let names = client.label_names().start(1).get().await?;
+        assert_eq!(names, vec![String::from("up")]);
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method(), HttpMethod::GET);
+        assert_eq!(
+            requests[0].url().as_str(),
+            "http://prometheus.example.com/api/v1/labels?start=1"
+        );
+        assert_eq!(requests[0].headers()[AUTHORIZATION], "Bearer token");
+        assert_eq!(requests[0].body(), None);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_retry_transport_errors() -> Result<(), anyhow::Error> {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        // Times out on every request but the third one.
+        let flaky = |attempts: &Arc<AtomicUsize>| {
+            let attempts = Arc::clone(attempts);
+            StaticTransport::new(move |_: &HttpRequest| {
+                if attempts.fetch_add(1, Ordering::SeqCst) + 1 == 3 {
+                    Ok(HttpResponse::new(StatusCode::OK, ""))
+                } else {
+                    Err(std::io::Error::from(std::io::ErrorKind::TimedOut).into())
+                }
             })
-        })?;
-        match response {
-            ApiResponse::Success { data } => Ok(data),
-            ApiResponse::Error(e) => Err(Error::Prometheus(e)),
+        };
+
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let policy = RetryPolicy::new().initial_backoff(Duration::from_millis(1));
+        let client = Client::builder()
+            .transport(flaky(&attempts))
+            .retry(policy.clone())
+            .build()?;
+
+        assert!(client.is_server_ready().await?);
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let client = Client::builder()
+            .transport(flaky(&attempts))
+            .retry(policy.retry_timeouts(false))
+            .build()?;
+
+        assert!(matches!(
+            client.is_server_ready().await,
+            Err(Error::Transport(_))
+        ));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_middleware() -> Result<(), anyhow::Error> {
+        use std::sync::Mutex;
+
+        struct Recorder {
+            name: &'static str,
+            events: Arc<Mutex<Vec<String>>>,
+        }
+
+        impl Middleware for Recorder {
+            fn before(&self, request: &mut MiddlewareRequest) -> Option<HttpResponse> {
+                self.events.lock().unwrap().push(format!(
+                    "before {} {} {} {:?}",
+                    self.name,
+                    request.method(),
+                    request.path(),
+                    request.params()
+                ));
+                request.header("X-Middleware", HeaderValue::from_static(self.name));
+                request
+                    .params_mut()
+                    .push((String::from("limit"), String::from("1")));
+                None
+            }
+
+            fn after(
+                &self,
+                request: &MiddlewareRequest,
+                response: Result<&MiddlewareResponse, &Error>,
+            ) {
+                self.events.lock().unwrap().push(format!(
+                    "after {} {} {}",
+                    self.name,
+                    request.path(),
+                    response.unwrap().status().as_u16()
+                ));
+            }
         }
+
+        struct Cache;
+
+        impl Middleware for Cache {
+            fn before(&self, _: &mut MiddlewareRequest) -> Option<HttpResponse> {
+                Some(json_response(r#"{"status":"success","data":["up"]}"#))
+            }
+        }
+
+        let ok = || raw_response("200 OK", r#"{"status":"success","data":["job"]}"#);
+
+        let events = Arc::new(Mutex::new(vec![]));
+        let recorder = |name| Recorder {
+            name,
+            events: Arc::clone(&events),
+        };
+
+        let (url, handle) = serve(vec![ok()]);
+        let client = Client::builder()
+            .url(&url)
+            .middleware(recorder("a"))
+            .middleware(recorder("b"))
+            .build()?;
+
+        let names = client.label_names().start(1).get().await?;
+        assert_eq!(names, vec![String::from("job")]);
+
+        let requests = handle.join().unwrap();
+        assert!(requests[0].starts_with("get /api/v1/labels?start=1&limit=1&limit=1 "));
+        assert!(requests[0].contains("x-middleware: b\r\n"));
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                r#"before a GET api/v1/labels [("start", "1")]"#,
+                r#"before b GET api/v1/labels [("start", "1"), ("limit", "1")]"#,
+                "after b api/v1/labels 200",
+                "after a api/v1/labels 200",
+            ]
+        );
+
+        events.lock().unwrap().clear();
+        let client = Client::builder()
+            .url("http://127.0.0.1:1")
+            .middleware(recorder("a"))
+            .middleware(Cache)
+            .middleware(recorder("b"))
+            .build()?;
+
+        let names = client.label_names().get().await?;
+        assert_eq!(names, vec![String::from("up")]);
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec!["before a GET api/v1/labels []", "after a api/v1/labels 200"]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_split_range_query() -> Result<(), anyhow::Error> {
+        // Answers range queries with one sample per step for two series.
+        let steps = StaticTransport::new(|request: &HttpRequest| {
+            let param = |name: &str| -> f64 {
+                request
+                    .url()
+                    .query_pairs()
+                    .find(|(k, _)| k == name)
+                    .unwrap()
+                    .1
+                    .parse()
+                    .unwrap()
+            };
+            let (start, end, step) = (param("start"), param("end"), param("step"));
+
+            let mut values = vec![];
+            let mut t = start;
+            while t <= end {
+                values.push(format!(r#"[{},"{}"]"#, t, t));
+                t += step;
+            }
+            let values = values.join(",");
+
+            Ok(json_response(format!(
+                r#"{{"status":"success","data":{{"resultType":"matrix","result":[{{"metric":{{"job":"a"}},"values":[{}]}},{{"metric":{{"job":"b"}},"values":[{}]}}]}}}}"#,
+                values, values
+            )))
+        });
+
+        let client = Client::builder().transport(steps).build()?;
+
+        let builder = client.query_range("up", 100, 200, 15.0).split(3);
+        assert_eq!(
+            builder.chunks(),
+            vec![(100.0, 130.0), (145.0, 175.0), (190.0, 190.0)]
+        );
+        assert_eq!(builder.clone().split(100).chunks(), vec![(100.0, 190.0)]);
+        assert_eq!(
+            client.query_range("up", 100, 200, "15s").split(3).chunks(),
+            builder.chunks()
+        );
+
+        let result = builder.split_concurrency(2).get().await?;
+        let matrix = result.data().as_matrix().unwrap();
+        assert_eq!(matrix.len(), 2);
+
+        for series in matrix {
+            let timestamps: Vec<f64> = series.samples().iter().map(|s| s.timestamp()).collect();
+            assert_eq!(
+                timestamps,
+                vec![100.0, 115.0, 130.0, 145.0, 160.0, 175.0, 190.0]
+            );
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_stream_responses() -> Result<(), anyhow::Error> {
+        let paths = StaticTransport::new(|request: &HttpRequest| {
+            Ok(json_response(match request.url().path() {
+                "/api/v1/query" | "/api/v1/query_range" => {
+                    r#"{"status":"success","data":{"resultType":"vector","result":[{"metric":{"job":"a"},"value":[1,"1"]},{"metric":{"job":"b"},"value":[1,"0"]}]}}"#
+                }
+                "/api/v1/series" => {
+                    r#"{"status":"success","data":[{"__name__":"up","job":"a"},{"__name__":"up","job":"b"}]}"#
+                }
+                _ => r#"{"status":"success","data":["a","b"]}"#,
+            }))
+        });
+
+        let client = Client::builder().transport(paths).build()?;
+
+        let vectors: Vec<InstantVector> =
+            client.query("up").get_stream().await?.try_collect().await?;
+        assert_eq!(vectors.len(), 2);
+        assert_eq!(vectors[1].metric()["job"], "b");
+
+        let mut stream = client.query_range("up", 1, 2, 1.0).get_stream().await?;
+        assert!(matches!(
+            stream.next().await,
+            Some(Err(Error::Deserialize(_)))
+        ));
+        assert!(stream.next().await.is_none());
+
+        let mut jobs = vec![];
+        client
+            .series([Selector::new().metric("up")])?
+            .for_each(|series| jobs.push(series["job"].clone()))
+            .await?;
+        assert_eq!(jobs, ["a", "b"]);
+
+        let mut values = vec![];
+        client
+            .label_values("job")
+            .for_each(|value| values.push(value))
+            .await?;
+        assert_eq!(values, ["a", "b"]);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_columnar_range_query() -> Result<(), anyhow::Error> {
+        // Returns the given samples of two series along with a warning.
+        let transport = |a: &'static str, b: &'static str| {
+            StaticTransport::new(move |_: &HttpRequest| {
+                Ok(json_response(format!(
+                    r#"{{"status":"success","data":{{"resultType":"matrix","result":[{{"metric":{{"job":"a"}},"values":[{}]}},{{"metric":{{"job":"b"}},"values":[{}]}}]}},"warnings":["partial"]}}"#,
+                    a, b
+                )))
+            })
+        };
+
+        let client = Client::builder()
+            .transport(transport(r#"[10,"1"],[20,"2"]"#, r#"[20,"3"]"#))
+            .build()?;
+        let matrix = client.query_range("up", 0, 30, 10.0).get_columnar().await?;
+        assert_eq!(matrix.timestamps(), [0.0, 10.0, 20.0, 30.0]);
+        assert_eq!(matrix.series()[0].values()[1..3], [1.0, 2.0]);
+        assert!(matrix.series()[1].values()[1].is_nan());
+        assert_eq!(matrix.series()[1].values()[2], 3.0);
+        assert_eq!(matrix.annotations().warnings(), ["partial"]);
+
+        // Samples off the grid fall back to the union of all timestamps.
+        let client = Client::builder()
+            .transport(transport(r#"[10,"1"],[20.5,"2"]"#, r#"[20,"3"]"#))
+            .build()?;
+        let matrix = client
+            .query_range("up", 0, 30, 10.0)
+            .post_columnar()
+            .await?;
+        assert_eq!(matrix.timestamps(), [10.0, 20.0, 20.5]);
+        assert_eq!(matrix.series()[0].values()[2], 2.0);
+
+        Ok(())
     }
 }
@@ -1,3 +1,4 @@
+use crate::arg::{ToQueryDuration, ToTimestamp};
 use crate::client::*;
 use crate::error::Error;
 use crate::response::*;
@@ -18,12 +19,12 @@ use std::str::FromStr;
 /// async fn main() -> Result<(), anyhow::Error> {
 ///     let q = "sum(prometheus_http_requests_total)";
 ///
-///     let response = query("http://localhost:9090", q)?.timeout(1000).get().await?;
+///     let response = query("http://localhost:9090", q)?.timeout("1s").get().await?;
 ///
 ///     assert!(response.data().as_vector().is_some());
 ///
 ///     // Or make a POST request.
-///     let response = query("http://localhost:9090", q)?.timeout(1000).post().await?;
+///     let response = query("http://localhost:9090", q)?.timeout("1s").post().await?;
 ///
 ///     assert!(response.data().as_vector().is_some());
 ///
@@ -60,9 +61,9 @@ pub fn query(host: &str, query: impl std::fmt::Display) -> Result<InstantQueryBu
 pub fn query_range(
     host: &str,
     query: impl std::fmt::Display,
-    start: i64,
-    end: i64,
-    step: f64,
+    start: impl ToTimestamp,
+    end: impl ToTimestamp,
+    step: impl ToQueryDuration,
 ) -> Result<RangeQueryBuilder, Error> {
     Client::from_str(host).map(|c| c.query_range(query, start, end, step))
 }
@@ -87,10 +88,10 @@ pub fn query_range(
 ///     Ok(())
 /// }
 /// ```
-pub fn series<'a, T>(host: &str, selectors: T) -> Result<SeriesQueryBuilder, Error>
+pub fn series<T>(host: &str, selectors: T) -> Result<SeriesQueryBuilder, Error>
 where
     T: IntoIterator,
-    T::Item: Borrow<Selector<'a>>,
+    T::Item: Borrow<Selector>,
 {
     Client::from_str(host).and_then(|c| c.series(selectors))
 }
@@ -258,6 +259,26 @@ pub async fn runtime_information(host: &str) -> Result<RuntimeInformation, Error
     Client::from_str(host)?.runtime_information().await
 }
 
+/// Retrieve the configuration that the Prometheus server is currently running with.
+///
+/// This is just a convenience function for one-off requests, see [`Client::config`].
+///
+/// ```rust
+/// use prometheus_http_query::config;
+///
+/// #[tokio::main(flavor = "current_thread")]
+/// async fn main() -> Result<(), anyhow::Error> {
+///     let response = config("http://localhost:9090").await;
+///
+///     assert!(response.is_ok());
+///
+///     Ok(())
+/// }
+/// ```
+pub async fn config(host: &str) -> Result<Config, Error> {
+    Client::from_str(host)?.config().await
+}
+
 /// Query the current state of alertmanager discovery.
 ///
 /// This is just a convenience function for one-off requests, see [`Client::alertmanagers`].
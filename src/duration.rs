@@ -0,0 +1,312 @@
+use crate::arg::{QueryDuration, ToQueryDuration};
+use crate::error::{Error, InvalidDurationError};
+use serde::{de::Error as SerdeError, Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+const MILLIS_PER_SECOND: i64 = 1000;
+const MILLIS_PER_MINUTE: i64 = 60 * MILLIS_PER_SECOND;
+const MILLIS_PER_HOUR: i64 = 60 * MILLIS_PER_MINUTE;
+const MILLIS_PER_DAY: i64 = 24 * MILLIS_PER_HOUR;
+const MILLIS_PER_WEEK: i64 = 7 * MILLIS_PER_DAY;
+const MILLIS_PER_YEAR: i64 = 365 * MILLIS_PER_DAY;
+
+// Prometheus stores durations as Go's `time.Duration`, i.e. as int64 nanoseconds.
+const MAX_MILLIS: i64 = i64::MAX / 1_000_000;
+
+// Units in the order in which they must appear in a duration string. Years and weeks
+// are only used for formatting when the duration is an exact multiple of them.
+const UNITS: [(&str, i64, bool); 7] = [
+    ("y", MILLIS_PER_YEAR, true),
+    ("w", MILLIS_PER_WEEK, true),
+    ("d", MILLIS_PER_DAY, false),
+    ("h", MILLIS_PER_HOUR, false),
+    ("m", MILLIS_PER_MINUTE, false),
+    ("s", MILLIS_PER_SECOND, false),
+    ("ms", 1, false),
+];
+
+/// A duration in the format that is used by Prometheus, e.g. `5m`, `1h30m` or `500ms`.
+///
+/// Durations have millisecond precision and are never negative. Parsing follows the rules of
+/// Prometheus, i.e. a duration consists of integers followed by one of the units `y`, `w`, `d`,
+/// `h`, `m`, `s` and `ms` in this order, each unit appearing at most once. Formatting produces the
+/// canonical form that Prometheus uses as well:
+///
+/// ```rust
+/// use prometheus_http_query::PrometheusDuration;
+/// use std::str::FromStr;
+///
+/// let duration = PrometheusDuration::from_str("90m").unwrap();
+/// assert_eq!(duration.to_string(), "1h30m");
+/// assert_eq!(std::time::Duration::from(duration).as_secs(), 5400);
+///
+/// let duration = PrometheusDuration::try_from(time::Duration::days(14)).unwrap();
+/// assert_eq!(duration.to_string(), "2w");
+///
+/// assert!(PrometheusDuration::from_str("1.5h").is_err());
+/// assert!(PrometheusDuration::from_str("30s5m").is_err());
+/// ```
+///
+/// A `PrometheusDuration` can be passed to the query builders wherever a [`ToQueryDuration`]
+/// is accepted and converts into a [`time::Duration`] for use with the [`expr`](crate::expr) module.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PrometheusDuration(i64);
+
+impl PrometheusDuration {
+    /// A duration of zero.
+    pub const ZERO: PrometheusDuration = PrometheusDuration(0);
+
+    /// Return the total number of whole milliseconds.
+    pub fn as_millis(&self) -> i64 {
+        self.0
+    }
+
+    /// Return the total number of seconds.
+    pub fn as_seconds_f64(&self) -> f64 {
+        self.0 as f64 / MILLIS_PER_SECOND as f64
+    }
+
+    fn from_millis(millis: i128, input: impl fmt::Display) -> Result<Self, Error> {
+        if millis < 0 {
+            return Err(invalid("duration must not be negative", input));
+        }
+        if millis > MAX_MILLIS as i128 {
+            return Err(invalid("duration is out of range", input));
+        }
+        Ok(PrometheusDuration(millis as i64))
+    }
+}
+
+fn invalid(message: &'static str, input: impl fmt::Display) -> Error {
+    Error::InvalidDuration(InvalidDurationError {
+        message,
+        input: input.to_string(),
+    })
+}
+
+impl FromStr for PrometheusDuration {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "0" {
+            return Ok(PrometheusDuration::ZERO);
+        }
+        if s.is_empty() {
+            return Err(invalid("empty duration string", s));
+        }
+
+        let mut total: i64 = 0;
+        let mut rest = s;
+        // Index into `UNITS` of the next unit that is allowed to appear.
+        let mut next = 0;
+
+        while !rest.is_empty() {
+            let digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+            if digits == 0 {
+                return Err(invalid("expected a number", s));
+            }
+
+            let value = rest[..digits]
+                .parse::<i64>()
+                .map_err(|_| invalid("duration is out of range", s))?;
+            rest = &rest[digits..];
+
+            // "ms" has to be tested before "m".
+            let unit = UNITS[next..]
+                .iter()
+                .enumerate()
+                .filter(|(_, (unit, _, _))| rest.starts_with(unit))
+                .max_by_key(|(_, (unit, _, _))| unit.len());
+
+            let (i, (unit, mult, _)) = match unit {
+                Some(u) => u,
+                None if UNITS.iter().any(|(unit, _, _)| rest.starts_with(unit)) => {
+                    return Err(invalid("units must appear once in descending order", s))
+                }
+                None => return Err(invalid("unknown or missing unit", s)),
+            };
+            rest = &rest[unit.len()..];
+            next += i + 1;
+
+            total = value
+                .checked_mul(*mult)
+                .and_then(|v| v.checked_add(total))
+                .filter(|v| *v <= MAX_MILLIS)
+                .ok_or_else(|| invalid("duration is out of range", s))?;
+        }
+
+        Ok(PrometheusDuration(total))
+    }
+}
+
+impl fmt::Display for PrometheusDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_millis(f, self.0 as u128)
+    }
+}
+
+// Writes a number of milliseconds using the largest units first and omitting units that
+// are zero, e.g. "1h30m". The remainder is checked by hand because `u128::is_multiple_of`
+// requires Rust 1.87.
+#[allow(clippy::manual_is_multiple_of)]
+pub(crate) fn write_millis(f: &mut fmt::Formatter<'_>, mut ms: u128) -> fmt::Result {
+    if ms == 0 {
+        return f.write_str("0s");
+    }
+
+    for (unit, mult, exact) in UNITS {
+        let mult = mult as u128;
+        if exact && ms % mult != 0 {
+            continue;
+        }
+        let v = ms / mult;
+        if v > 0 {
+            write!(f, "{}{}", v, unit)?;
+            ms -= v * mult;
+        }
+    }
+
+    Ok(())
+}
+
+impl From<PrometheusDuration> for time::Duration {
+    fn from(d: PrometheusDuration) -> Self {
+        time::Duration::milliseconds(d.0)
+    }
+}
+
+impl From<PrometheusDuration> for std::time::Duration {
+    fn from(d: PrometheusDuration) -> Self {
+        std::time::Duration::from_millis(d.0 as u64)
+    }
+}
+
+/// Fails if the duration is negative or exceeds the range Prometheus supports.
+/// Precision beyond milliseconds is truncated.
+impl TryFrom<time::Duration> for PrometheusDuration {
+    type Error = Error;
+
+    fn try_from(d: time::Duration) -> Result<Self, Self::Error> {
+        PrometheusDuration::from_millis(d.whole_milliseconds(), d)
+    }
+}
+
+/// Fails if the duration exceeds the range Prometheus supports.
+/// Precision beyond milliseconds is truncated.
+impl TryFrom<std::time::Duration> for PrometheusDuration {
+    type Error = Error;
+
+    fn try_from(d: std::time::Duration) -> Result<Self, Self::Error> {
+        PrometheusDuration::from_millis(
+            d.as_millis().try_into().unwrap_or(i128::MAX),
+            format_args!("{:?}", d),
+        )
+    }
+}
+
+impl Serialize for PrometheusDuration {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for PrometheusDuration {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw_str = String::deserialize(deserializer)?;
+        raw_str.parse().map_err(SerdeError::custom)
+    }
+}
+
+impl ToQueryDuration for PrometheusDuration {
+    fn to_query_duration(self) -> QueryDuration {
+        self.as_seconds_f64().to_query_duration()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Result<i64, Error> {
+        s.parse::<PrometheusDuration>().map(|d| d.as_millis())
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse("0").unwrap(), 0);
+        assert_eq!(parse("0s").unwrap(), 0);
+        assert_eq!(parse("500ms").unwrap(), 500);
+        assert_eq!(parse("5m").unwrap(), 5 * MILLIS_PER_MINUTE);
+        assert_eq!(
+            parse("1h30m").unwrap(),
+            MILLIS_PER_HOUR + 30 * MILLIS_PER_MINUTE
+        );
+        assert_eq!(
+            parse("1y2w3d4h5m6s7ms").unwrap(),
+            MILLIS_PER_YEAR
+                + 2 * MILLIS_PER_WEEK
+                + 3 * MILLIS_PER_DAY
+                + 4 * MILLIS_PER_HOUR
+                + 5 * MILLIS_PER_MINUTE
+                + 6 * MILLIS_PER_SECOND
+                + 7
+        );
+        assert_eq!(parse("1m500ms").unwrap(), MILLIS_PER_MINUTE + 500);
+
+        for invalid in [
+            "", "1", "1.5h", "-5m", "5", "m", "1x", "30s5m", "5m5m", "1ms1s", " 5m", "5m ", "300y",
+        ] {
+            assert!(
+                matches!(parse(invalid), Err(Error::InvalidDuration(_))),
+                "{:?} should be invalid",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_format_duration() {
+        for (input, expected) in [
+            ("0s", "0s"),
+            ("90m", "1h30m"),
+            ("1500ms", "1s500ms"),
+            ("14d", "2w"),
+            ("15d", "15d"),
+            ("365d", "1y"),
+            ("366d", "366d"),
+            ("1d1h1m1s1ms", "1d1h1m1s1ms"),
+        ] {
+            assert_eq!(
+                input.parse::<PrometheusDuration>().unwrap().to_string(),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_convert_duration() {
+        let d = PrometheusDuration::try_from(time::Duration::seconds(90)).unwrap();
+        assert_eq!(d.to_string(), "1m30s");
+        assert_eq!(time::Duration::from(d), time::Duration::seconds(90));
+        assert_eq!(
+            std::time::Duration::from(d),
+            std::time::Duration::from_secs(90)
+        );
+        assert_eq!(
+            PrometheusDuration::try_from(std::time::Duration::from_micros(1500)).unwrap(),
+            PrometheusDuration(1)
+        );
+        assert!(PrometheusDuration::try_from(time::Duration::seconds(-1)).is_err());
+        assert!(PrometheusDuration::try_from(std::time::Duration::MAX).is_err());
+        assert_eq!(d.to_query_duration().seconds(), Some(90.0));
+    }
+
+    #[test]
+    fn test_serde_duration() {
+        let d: PrometheusDuration = serde_json::from_str("\"2h\"").unwrap();
+        assert_eq!(d.as_millis(), 2 * MILLIS_PER_HOUR);
+        assert_eq!(serde_json::to_string(&d).unwrap(), "\"2h\"");
+        assert!(serde_json::from_str::<PrometheusDuration>("\"2x\"").is_err());
+    }
+}
@@ -20,8 +20,35 @@ pub enum Error {
     /// series [`Selector`](crate::selector::Selector)s. According to the Prometheus API description at least one
     /// [`Selector`](crate::selector::Selector) must be provided.
     EmptySeriesSelector,
+    /// Occurs when a [`MultiClient`](crate::MultiClient) is created without any endpoint URL.
+    EmptyEndpoints,
     /// Wraps errors from the [`url`] crate.
     ParseUrl(ParseUrlError),
+    /// Occurs when one of the fallible [`Selector`](crate::selector::Selector) methods
+    /// (e.g. [`Selector::try_eq`](crate::selector::Selector::try_eq)) is called with an invalid
+    /// metric or label name.
+    InvalidSelector(InvalidSelectorError),
+    /// Occurs when a string cannot be parsed into a [`Selector`](crate::selector::Selector).
+    ParseSelector(ParseSelectorError),
+    /// Occurs when a string cannot be parsed into a [`PrometheusDuration`](crate::PrometheusDuration)
+    /// or a duration is out of its range.
+    InvalidDuration(InvalidDurationError),
+    /// Occurs when the credentials that are configured for the [`Client`](crate::Client)
+    /// cannot be obtained, e.g. because a token file cannot be read.
+    Auth(AuthError),
+    /// Occurs when an OAuth2 access token cannot be obtained from the token endpoint.
+    OAuth2(OAuth2Error),
+    /// Occurs when a request cannot be sent or no response is received, e.g. due to a network
+    /// error or a timeout. This applies to the default [`reqwest::Client`] and custom
+    /// [`Transport`](crate::Transport)s alike.
+    Transport(TransportError),
+    /// Occurs when a response that is parsed incrementally (see [`SeriesStream`](crate::SeriesStream))
+    /// is malformed or does not match the expected type.
+    Deserialize(DeserializeError),
+    /// Occurs when the runtime that drives the requests of a [`blocking::Client`](crate::blocking::Client)
+    /// cannot be created (requires the `blocking` feature).
+    #[cfg(feature = "blocking")]
+    Runtime(RuntimeError),
 }
 
 impl fmt::Display for Error {
@@ -30,7 +57,17 @@ impl fmt::Display for Error {
             Self::Client(e) => e.fmt(f),
             Self::Prometheus(e) => e.fmt(f),
             Self::EmptySeriesSelector => f.write_str("at least one series selector must be provided in order to query the series endpoint"),
+            Self::EmptyEndpoints => f.write_str("at least one endpoint URL must be provided"),
             Self::ParseUrl(e) => e.fmt(f),
+            Self::InvalidSelector(e) => e.fmt(f),
+            Self::ParseSelector(e) => e.fmt(f),
+            Self::InvalidDuration(e) => e.fmt(f),
+            Self::Auth(e) => e.fmt(f),
+            Self::OAuth2(e) => e.fmt(f),
+            Self::Transport(e) => e.fmt(f),
+            Self::Deserialize(e) => e.fmt(f),
+            #[cfg(feature = "blocking")]
+            Self::Runtime(e) => e.fmt(f),
         }
     }
 }
@@ -41,7 +78,17 @@ impl StdError for Error {
             Self::Client(e) => e.source(),
             Self::Prometheus(_) => None,
             Self::EmptySeriesSelector => None,
+            Self::EmptyEndpoints => None,
             Self::ParseUrl(e) => e.source(),
+            Self::InvalidSelector(_) => None,
+            Self::ParseSelector(_) => None,
+            Self::InvalidDuration(_) => None,
+            Self::Auth(e) => e.source(),
+            Self::OAuth2(e) => e.source(),
+            Self::Transport(e) => e.source(),
+            Self::Deserialize(e) => e.source(),
+            #[cfg(feature = "blocking")]
+            Self::Runtime(e) => e.source(),
         }
     }
 }
@@ -102,6 +149,38 @@ impl PrometheusError {
     pub fn is_not_found(&self) -> bool {
         self.error_type == PrometheusErrorType::NotFound
     }
+
+    /// Returns the position within the PromQL query at which parsing failed, or `None`
+    /// if this error is not a PromQL parse error.
+    pub fn position(&self) -> Option<Position> {
+        // Parse errors are reported like "1:14: parse error: unexpected end of input in aggregation",
+        // possibly prefixed with additional context like 'invalid parameter "query": '.
+        let end = self.message.find(": parse error")?;
+        let (line, column) = self.message[..end].rsplit(' ').next()?.split_once(':')?;
+        Some(Position {
+            line: line.parse().ok()?,
+            column: column.parse().ok()?,
+        })
+    }
+}
+
+/// The position within a PromQL query at which parsing failed, see [`PrometheusError::position`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+}
+
+impl Position {
+    /// Returns the line number (starting at 1).
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Returns the column number (starting at 1).
+    pub fn column(&self) -> usize {
+        self.column
+    }
 }
 
 /// The parsed error type as returned by the Prometheus API.
@@ -139,31 +218,39 @@ impl fmt::Display for PrometheusErrorType {
 }
 
 /// Is thrown when the [`Client`](crate::Client) or the underlying
-/// [`reqwest::Error`] fail to build or execute a request.
+/// [`reqwest::Error`] fail to build a request or to process its response.
+/// Errors while sending a request are reported as [`TransportError`].
 #[derive(Debug)]
 pub struct ClientError {
     pub(crate) message: &'static str,
-    pub(crate) source: Option<reqwest::Error>,
+    pub(crate) source: Option<Box<dyn StdError + Send + Sync>>,
 }
 
 impl fmt::Display for ClientError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.write_str(&self.message)
+        f.write_str(self.message)
     }
 }
 
 impl StdError for ClientError {
     fn source(&self) -> Option<&(dyn StdError + 'static)> {
-        self.source.as_ref().map(|e| e as &dyn StdError)
+        self.source.as_deref().map(|e| e as &dyn StdError)
     }
 }
 
 impl ClientError {
-    /// Obtain the [`reqwest::Error`] that is the actual cause of this
-    /// error or `None` if the error originated in [`Client`](crate::Client)
-    /// itself.<br>
-    pub fn inner(&self) -> Option<&reqwest::Error> {
-        self.source.as_ref()
+    /// Obtain the error that is the actual cause of this error or `None`
+    /// if the error originated in [`Client`](crate::Client) itself.
+    pub fn inner(&self) -> Option<&(dyn StdError + Send + Sync + 'static)> {
+        self.source.as_deref()
+    }
+
+    /// Obtain the [`reqwest::Error`] that is the actual cause of this error, if any.
+    ///
+    /// Note that this exposes the HTTP client that is used internally, so the type
+    /// of the error may change whenever the `reqwest` dependency is upgraded.
+    pub fn reqwest_error(&self) -> Option<&reqwest::Error> {
+        self.inner()?.downcast_ref()
     }
 }
 
@@ -177,7 +264,7 @@ pub struct ParseUrlError {
 
 impl fmt::Display for ParseUrlError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.write_str(&self.message)
+        f.write_str(self.message)
     }
 }
 
@@ -193,3 +280,250 @@ impl ParseUrlError {
         &self.source
     }
 }
+
+/// Is thrown when a metric or label name that is passed to a fallible
+/// [`Selector`](crate::selector::Selector) method is invalid.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidSelectorError {
+    pub(crate) message: &'static str,
+    pub(crate) name: String,
+}
+
+impl fmt::Display for InvalidSelectorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: \"{}\"", self.message, self.name)
+    }
+}
+
+impl StdError for InvalidSelectorError {}
+
+impl InvalidSelectorError {
+    /// Returns the metric or label name that failed validation.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Is thrown when a string cannot be parsed into a [`Selector`](crate::selector::Selector).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseSelectorError {
+    pub(crate) message: &'static str,
+    pub(crate) position: usize,
+}
+
+impl fmt::Display for ParseSelectorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl StdError for ParseSelectorError {}
+
+impl ParseSelectorError {
+    /// Returns the byte offset within the input at which parsing failed.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+/// Is thrown when a [`PrometheusDuration`](crate::PrometheusDuration) cannot be parsed or
+/// a duration cannot be converted into one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidDurationError {
+    pub(crate) message: &'static str,
+    pub(crate) input: String,
+}
+
+impl fmt::Display for InvalidDurationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: \"{}\"", self.message, self.input)
+    }
+}
+
+impl StdError for InvalidDurationError {}
+
+impl InvalidDurationError {
+    /// Returns the input that failed validation.
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+}
+
+/// Is thrown when the credentials that are configured for the [`Client`](crate::Client)
+/// cannot be obtained.
+#[derive(Debug)]
+pub struct AuthError {
+    pub(crate) message: &'static str,
+    pub(crate) source: Option<std::io::Error>,
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.message)
+    }
+}
+
+impl StdError for AuthError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        self.source.as_ref().map(|e| e as &dyn StdError)
+    }
+}
+
+impl AuthError {
+    /// Obtain the [`std::io::Error`] that is the actual cause of this error
+    /// or `None` if the credentials are missing altogether.
+    pub fn inner(&self) -> Option<&std::io::Error> {
+        self.source.as_ref()
+    }
+}
+
+/// Is thrown when an OAuth2 access token cannot be obtained from the token endpoint.
+#[derive(Debug)]
+pub struct OAuth2Error {
+    pub(crate) message: &'static str,
+    pub(crate) source: Option<Box<dyn StdError + Send + Sync>>,
+    pub(crate) details: Option<String>,
+}
+
+impl fmt::Display for OAuth2Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.details {
+            Some(details) => write!(f, "{}: {}", self.message, details),
+            None => f.write_str(self.message),
+        }
+    }
+}
+
+impl StdError for OAuth2Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        self.source.as_deref().map(|e| e as &dyn StdError)
+    }
+}
+
+impl OAuth2Error {
+    /// Obtain the error that is the actual cause of this error
+    /// or `None` if the token endpoint returned an error response.
+    pub fn inner(&self) -> Option<&(dyn StdError + Send + Sync + 'static)> {
+        self.source.as_deref()
+    }
+
+    /// Returns the error (and its description if any) as reported by the token endpoint.
+    pub fn details(&self) -> Option<&str> {
+        self.details.as_deref()
+    }
+}
+
+/// Is thrown when the [`reqwest::Client`] or a custom [`Transport`](crate::Transport) fails to send a request.
+#[derive(Debug)]
+pub struct TransportError {
+    pub(crate) message: &'static str,
+    pub(crate) source: Box<dyn StdError + Send + Sync>,
+}
+
+impl fmt::Display for TransportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.message)
+    }
+}
+
+impl StdError for TransportError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        Some(self.source.as_ref())
+    }
+}
+
+impl TransportError {
+    /// Obtain the error that was returned by the transport.
+    pub fn inner(&self) -> &(dyn StdError + Send + Sync + 'static) {
+        self.source.as_ref()
+    }
+}
+
+/// Is thrown when a response that is parsed incrementally is malformed or does not
+/// match the expected type.
+#[derive(Debug)]
+pub struct DeserializeError {
+    pub(crate) message: &'static str,
+    pub(crate) source: Option<serde_json::Error>,
+}
+
+impl fmt::Display for DeserializeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.message)
+    }
+}
+
+impl StdError for DeserializeError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        self.source.as_ref().map(|e| e as &dyn StdError)
+    }
+}
+
+impl DeserializeError {
+    /// Obtain the [`serde_json::Error`] that is the actual cause of this error or `None`
+    /// if the structure of the response was invalid.
+    pub fn inner(&self) -> Option<&serde_json::Error> {
+        self.source.as_ref()
+    }
+}
+
+/// Is thrown when the runtime that drives the requests of a [`blocking::Client`](crate::blocking::Client)
+/// cannot be created.
+#[cfg(feature = "blocking")]
+#[derive(Debug)]
+pub struct RuntimeError {
+    pub(crate) message: &'static str,
+    pub(crate) source: std::io::Error,
+}
+
+#[cfg(feature = "blocking")]
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.message)
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl StdError for RuntimeError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        Some(&self.source)
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl RuntimeError {
+    /// Obtain the [`std::io::Error`] that is the actual cause of this error.
+    pub fn inner(&self) -> &std::io::Error {
+        &self.source
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_error_position() {
+        let error = PrometheusError {
+            error_type: PrometheusErrorType::BadData,
+            message: String::from(
+                "invalid parameter \"query\": 1:14: parse error: unexpected end of input in aggregation",
+            ),
+        };
+        let position = error.position().unwrap();
+        assert_eq!(position.line(), 1);
+        assert_eq!(position.column(), 14);
+
+        let error = PrometheusError {
+            error_type: PrometheusErrorType::BadData,
+            message: String::from("2:3: parse error: unexpected <op:+>"),
+        };
+        assert_eq!(error.position(), Some(Position { line: 2, column: 3 }));
+
+        let error = PrometheusError {
+            error_type: PrometheusErrorType::Execution,
+            message: String::from("query processing would load too many samples into memory"),
+        };
+        assert!(error.position().is_none());
+    }
+}
@@ -0,0 +1,1215 @@
+//! A typed builder for PromQL expressions.
+//!
+//! All expression types implement [`Display`](std::fmt::Display) and can therefore be passed to
+//! [`Client::query`](crate::Client::query) and [`Client::query_range`](crate::Client::query_range)
+//! just like a hand-written query string. Ranges and offsets accept a [`time::Duration`] or a
+//! [`PrometheusDuration`](crate::PrometheusDuration).
+//!
+//! ```rust
+//! use prometheus_http_query::expr::{histogram_quantile, rate, sum, VectorSelector};
+//! use prometheus_http_query::Selector;
+//! use time::Duration;
+//!
+//! let select = Selector::new()
+//!     .metric("http_request_duration_seconds_bucket")
+//!     .eq("job", "api");
+//!
+//! let q = histogram_quantile(
+//!     0.9,
+//!     sum(rate(VectorSelector::from(select).range(Duration::minutes(5)))).by(["le"]),
+//! );
+//!
+//! assert_eq!(
+//!     q.to_string(),
+//!     r#"histogram_quantile(0.9, sum by (le) (rate({__name__="http_request_duration_seconds_bucket",job="api"}[5m])))"#
+//! );
+//! ```
+use crate::duration::write_millis;
+use crate::selector::Selector;
+use crate::util::{write_label_name, write_quoted};
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Rem, Sub};
+use time::Duration;
+
+/// An instant vector, scalar or string expression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expr {
+    kind: ExprKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum ExprKind {
+    Number(f64),
+    String(String),
+    Selector(VectorSelector),
+    Call(String, Vec<FunctionArg>),
+    Aggregation(Aggregation),
+    Binary(BinaryExpr),
+    Negation(Box<Expr>),
+}
+
+impl Expr {
+    /// Create a number literal.
+    pub fn number(value: f64) -> Self {
+        Expr {
+            kind: ExprKind::Number(value),
+        }
+    }
+
+    /// Create a string literal.
+    pub fn string(value: impl std::fmt::Display) -> Self {
+        Expr {
+            kind: ExprKind::String(value.to_string()),
+        }
+    }
+
+    /// Turn this expression into a subquery that evaluates it over the given range.
+    /// PromQL equivalent: `rate(http_requests_total[5m])[30m:1m]`
+    ///
+    /// If `step` is `None` the global evaluation interval is used as resolution.
+    pub fn subquery(self, range: impl Into<Duration>, step: Option<Duration>) -> RangeExpr {
+        RangeExpr {
+            kind: RangeKind::Subquery(Box::new(self), step),
+            range: range.into(),
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    /// Compare this expression to another expression using the `==` operator.
+    pub fn equal_to(self, rhs: impl Into<Expr>) -> BinaryExpr {
+        BinaryExpr::new(self, BinaryOp::Equal, rhs.into())
+    }
+
+    /// Compare this expression to another expression using the `!=` operator.
+    pub fn not_equal_to(self, rhs: impl Into<Expr>) -> BinaryExpr {
+        BinaryExpr::new(self, BinaryOp::NotEqual, rhs.into())
+    }
+
+    /// Compare this expression to another expression using the `>` operator.
+    pub fn greater_than(self, rhs: impl Into<Expr>) -> BinaryExpr {
+        BinaryExpr::new(self, BinaryOp::Greater, rhs.into())
+    }
+
+    /// Compare this expression to another expression using the `>=` operator.
+    pub fn greater_or_equal(self, rhs: impl Into<Expr>) -> BinaryExpr {
+        BinaryExpr::new(self, BinaryOp::GreaterEqual, rhs.into())
+    }
+
+    /// Compare this expression to another expression using the `<` operator.
+    pub fn less_than(self, rhs: impl Into<Expr>) -> BinaryExpr {
+        BinaryExpr::new(self, BinaryOp::Less, rhs.into())
+    }
+
+    /// Compare this expression to another expression using the `<=` operator.
+    pub fn less_or_equal(self, rhs: impl Into<Expr>) -> BinaryExpr {
+        BinaryExpr::new(self, BinaryOp::LessEqual, rhs.into())
+    }
+
+    /// Raise this expression to the power of another expression (`^`).
+    pub fn pow(self, rhs: impl Into<Expr>) -> BinaryExpr {
+        BinaryExpr::new(self, BinaryOp::Pow, rhs.into())
+    }
+
+    /// Apply the `atan2` operator to this expression and another expression.
+    pub fn atan2(self, rhs: impl Into<Expr>) -> BinaryExpr {
+        BinaryExpr::new(self, BinaryOp::Atan2, rhs.into())
+    }
+
+    /// Intersect this expression with another expression (`and`).
+    pub fn and(self, rhs: impl Into<Expr>) -> BinaryExpr {
+        BinaryExpr::new(self, BinaryOp::And, rhs.into())
+    }
+
+    /// Union this expression with another expression (`or`).
+    pub fn or(self, rhs: impl Into<Expr>) -> BinaryExpr {
+        BinaryExpr::new(self, BinaryOp::Or, rhs.into())
+    }
+
+    /// Complement this expression with another expression (`unless`).
+    pub fn unless(self, rhs: impl Into<Expr>) -> BinaryExpr {
+        BinaryExpr::new(self, BinaryOp::Unless, rhs.into())
+    }
+
+    // Binary expressions and negations are wrapped in parentheses when used as an operand
+    // in order to preserve the intended evaluation order regardless of operator precedence.
+    fn needs_parens(&self) -> bool {
+        matches!(self.kind, ExprKind::Binary(_) | ExprKind::Negation(_))
+    }
+
+    fn fmt_operand(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.needs_parens() {
+            write!(f, "({})", self)
+        } else {
+            write!(f, "{}", self)
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ExprKind::Number(n) => write_number(f, *n),
+            ExprKind::String(s) => write_quoted(f, s),
+            ExprKind::Selector(s) => s.fmt(f),
+            ExprKind::Call(name, args) => {
+                write!(f, "{}(", name)?;
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    arg.fmt(f)?;
+                }
+                f.write_str(")")
+            }
+            ExprKind::Aggregation(a) => a.fmt(f),
+            ExprKind::Binary(b) => b.fmt(f),
+            ExprKind::Negation(e) => {
+                f.write_str("-")?;
+                e.fmt_operand(f)
+            }
+        }
+    }
+}
+
+impl From<f64> for Expr {
+    fn from(value: f64) -> Self {
+        Expr::number(value)
+    }
+}
+
+impl From<Selector> for Expr {
+    fn from(selector: Selector) -> Self {
+        Expr::from(VectorSelector::from(selector))
+    }
+}
+
+impl From<&Selector> for Expr {
+    fn from(selector: &Selector) -> Self {
+        Expr::from(VectorSelector::from(selector))
+    }
+}
+
+impl From<VectorSelector> for Expr {
+    fn from(selector: VectorSelector) -> Self {
+        Expr {
+            kind: ExprKind::Selector(selector),
+        }
+    }
+}
+
+impl From<Aggregation> for Expr {
+    fn from(aggregation: Aggregation) -> Self {
+        Expr {
+            kind: ExprKind::Aggregation(aggregation),
+        }
+    }
+}
+
+impl From<BinaryExpr> for Expr {
+    fn from(binary: BinaryExpr) -> Self {
+        Expr {
+            kind: ExprKind::Binary(binary),
+        }
+    }
+}
+
+/// An instant vector selector, i.e. a [`Selector`] that may be modified by
+/// an offset or a fixed evaluation time.
+/// PromQL equivalent: `http_requests_total{job="api"} offset 5m @ 1609746000`
+#[derive(Debug, Clone, PartialEq)]
+pub struct VectorSelector {
+    selector: Selector,
+    modifiers: Modifiers,
+}
+
+impl VectorSelector {
+    /// Create a new vector selector from a [`Selector`].
+    pub fn new(selector: &Selector) -> Self {
+        VectorSelector {
+            selector: selector.clone(),
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    /// Shift the evaluation time of this selector by the given duration.
+    /// Negative durations shift the evaluation time forward.
+    /// Calling this repeatedly will replace the current offset.
+    pub fn offset(mut self, offset: impl Into<Duration>) -> Self {
+        self.modifiers.offset = Some(offset.into());
+        self
+    }
+
+    /// Fix the evaluation time of this selector (`@` modifier).
+    /// Calling this repeatedly will replace the current evaluation time.
+    pub fn at(mut self, at: impl Into<At>) -> Self {
+        self.modifiers.at = Some(at.into());
+        self
+    }
+
+    /// Turn this selector into a range vector selector that selects all samples
+    /// within the given range.
+    /// PromQL equivalent: `http_requests_total{job="api"}[5m]`
+    pub fn range(self, range: impl Into<Duration>) -> RangeExpr {
+        RangeExpr {
+            kind: RangeKind::Selector(self.selector),
+            range: range.into(),
+            modifiers: self.modifiers,
+        }
+    }
+}
+
+impl From<Selector> for VectorSelector {
+    fn from(selector: Selector) -> Self {
+        VectorSelector {
+            selector,
+            modifiers: Modifiers::default(),
+        }
+    }
+}
+
+impl From<&Selector> for VectorSelector {
+    fn from(selector: &Selector) -> Self {
+        VectorSelector::new(selector)
+    }
+}
+
+impl fmt::Display for VectorSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.selector.fmt(f)?;
+        self.modifiers.fmt(f)
+    }
+}
+
+/// A range vector expression, i.e. a range vector selector or a subquery.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RangeExpr {
+    kind: RangeKind,
+    range: Duration,
+    modifiers: Modifiers,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum RangeKind {
+    Selector(Selector),
+    Subquery(Box<Expr>, Option<Duration>),
+}
+
+impl RangeExpr {
+    /// Shift the evaluation time of this expression by the given duration.
+    /// Negative durations shift the evaluation time forward.
+    /// Calling this repeatedly will replace the current offset.
+    pub fn offset(mut self, offset: impl Into<Duration>) -> Self {
+        self.modifiers.offset = Some(offset.into());
+        self
+    }
+
+    /// Fix the evaluation time of this expression (`@` modifier).
+    /// Calling this repeatedly will replace the current evaluation time.
+    pub fn at(mut self, at: impl Into<At>) -> Self {
+        self.modifiers.at = Some(at.into());
+        self
+    }
+}
+
+impl fmt::Display for RangeExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            RangeKind::Selector(selector) => {
+                selector.fmt(f)?;
+                f.write_str("[")?;
+                write_duration(f, self.range)?;
+                f.write_str("]")?;
+            }
+            RangeKind::Subquery(expr, step) => {
+                expr.fmt_operand(f)?;
+                f.write_str("[")?;
+                write_duration(f, self.range)?;
+                f.write_str(":")?;
+                if let Some(step) = step {
+                    write_duration(f, *step)?;
+                }
+                f.write_str("]")?;
+            }
+        }
+        self.modifiers.fmt(f)
+    }
+}
+
+/// The fixed evaluation time of a selector or subquery as set by the `@` modifier.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum At {
+    /// A Unix timestamp in seconds.
+    Timestamp(f64),
+    /// The start of the range query (`@ start()`).
+    Start,
+    /// The end of the range query or the evaluation time of the instant query (`@ end()`).
+    End,
+}
+
+impl From<i64> for At {
+    fn from(timestamp: i64) -> Self {
+        At::Timestamp(timestamp as f64)
+    }
+}
+
+impl From<f64> for At {
+    fn from(timestamp: f64) -> Self {
+        At::Timestamp(timestamp)
+    }
+}
+
+impl fmt::Display for At {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            At::Timestamp(t) => write!(f, "@ {}", t),
+            At::Start => f.write_str("@ start()"),
+            At::End => f.write_str("@ end()"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Modifiers {
+    offset: Option<Duration>,
+    at: Option<At>,
+}
+
+impl fmt::Display for Modifiers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(offset) = self.offset {
+            f.write_str(" offset ")?;
+            write_duration(f, offset)?;
+        }
+        if let Some(at) = self.at {
+            write!(f, " {}", at)?;
+        }
+        Ok(())
+    }
+}
+
+/// An argument of a function call, either an instant vector/scalar/string
+/// expression or a range vector expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FunctionArg {
+    Expr(Expr),
+    Range(RangeExpr),
+}
+
+impl fmt::Display for FunctionArg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FunctionArg::Expr(e) => e.fmt(f),
+            FunctionArg::Range(r) => r.fmt(f),
+        }
+    }
+}
+
+impl From<Expr> for FunctionArg {
+    fn from(expr: Expr) -> Self {
+        FunctionArg::Expr(expr)
+    }
+}
+
+impl From<RangeExpr> for FunctionArg {
+    fn from(range: RangeExpr) -> Self {
+        FunctionArg::Range(range)
+    }
+}
+
+impl From<f64> for FunctionArg {
+    fn from(value: f64) -> Self {
+        FunctionArg::Expr(Expr::number(value))
+    }
+}
+
+impl From<VectorSelector> for FunctionArg {
+    fn from(selector: VectorSelector) -> Self {
+        FunctionArg::Expr(selector.into())
+    }
+}
+
+impl From<Aggregation> for FunctionArg {
+    fn from(aggregation: Aggregation) -> Self {
+        FunctionArg::Expr(aggregation.into())
+    }
+}
+
+impl From<BinaryExpr> for FunctionArg {
+    fn from(binary: BinaryExpr) -> Self {
+        FunctionArg::Expr(binary.into())
+    }
+}
+
+/// Call an arbitrary PromQL function by name. Prefer the typed functions of this
+/// module where available.
+///
+/// ```rust
+/// use prometheus_http_query::expr::{function, FunctionArg, VectorSelector};
+/// use prometheus_http_query::Selector;
+/// use time::Duration;
+///
+/// let range = VectorSelector::from(Selector::new().metric("queue_size")).range(Duration::hours(1));
+/// let args: [FunctionArg; 3] = [range.into(), 0.3.into(), 0.1.into()];
+/// let q = function("double_exponential_smoothing", args);
+///
+/// assert_eq!(
+///     q.to_string(),
+///     r#"double_exponential_smoothing({__name__="queue_size"}[1h], 0.3, 0.1)"#
+/// );
+/// ```
+pub fn function<T>(name: impl std::fmt::Display, args: T) -> Expr
+where
+    T: IntoIterator,
+    T::Item: Into<FunctionArg>,
+{
+    Expr {
+        kind: ExprKind::Call(name.to_string(), args.into_iter().map(Into::into).collect()),
+    }
+}
+
+macro_rules! range_functions {
+    ($($(#[$doc:meta])* $name:ident),* $(,)?) => {
+        $(
+            $(#[$doc])*
+            pub fn $name(range: RangeExpr) -> Expr {
+                function(stringify!($name), [range])
+            }
+        )*
+    };
+}
+
+range_functions! {
+    /// PromQL function `rate(v range-vector)`.
+    rate,
+    /// PromQL function `irate(v range-vector)`.
+    irate,
+    /// PromQL function `increase(v range-vector)`.
+    increase,
+    /// PromQL function `delta(v range-vector)`.
+    delta,
+    /// PromQL function `idelta(v range-vector)`.
+    idelta,
+    /// PromQL function `deriv(v range-vector)`.
+    deriv,
+    /// PromQL function `changes(v range-vector)`.
+    changes,
+    /// PromQL function `resets(v range-vector)`.
+    resets,
+    /// PromQL function `avg_over_time(v range-vector)`.
+    avg_over_time,
+    /// PromQL function `min_over_time(v range-vector)`.
+    min_over_time,
+    /// PromQL function `max_over_time(v range-vector)`.
+    max_over_time,
+    /// PromQL function `sum_over_time(v range-vector)`.
+    sum_over_time,
+    /// PromQL function `count_over_time(v range-vector)`.
+    count_over_time,
+    /// PromQL function `last_over_time(v range-vector)`.
+    last_over_time,
+    /// PromQL function `stddev_over_time(v range-vector)`.
+    stddev_over_time,
+    /// PromQL function `stdvar_over_time(v range-vector)`.
+    stdvar_over_time,
+    /// PromQL function `present_over_time(v range-vector)`.
+    present_over_time,
+    /// PromQL function `absent_over_time(v range-vector)`.
+    absent_over_time,
+}
+
+macro_rules! instant_functions {
+    ($($(#[$doc:meta])* $name:ident),* $(,)?) => {
+        $(
+            $(#[$doc])*
+            pub fn $name(expr: impl Into<Expr>) -> Expr {
+                function(stringify!($name), [expr.into()])
+            }
+        )*
+    };
+}
+
+instant_functions! {
+    /// PromQL function `abs(v instant-vector)`.
+    abs,
+    /// PromQL function `absent(v instant-vector)`.
+    absent,
+    /// PromQL function `ceil(v instant-vector)`.
+    ceil,
+    /// PromQL function `floor(v instant-vector)`.
+    floor,
+    /// PromQL function `exp(v instant-vector)`.
+    exp,
+    /// PromQL function `ln(v instant-vector)`.
+    ln,
+    /// PromQL function `log2(v instant-vector)`.
+    log2,
+    /// PromQL function `log10(v instant-vector)`.
+    log10,
+    /// PromQL function `sqrt(v instant-vector)`.
+    sqrt,
+    /// PromQL function `round(v instant-vector)`.
+    round,
+    /// PromQL function `scalar(v instant-vector)`.
+    scalar,
+    /// PromQL function `sort(v instant-vector)`.
+    sort,
+    /// PromQL function `sort_desc(v instant-vector)`.
+    sort_desc,
+    /// PromQL function `timestamp(v instant-vector)`.
+    timestamp,
+    /// PromQL function `histogram_count(v instant-vector)`.
+    histogram_count,
+    /// PromQL function `histogram_sum(v instant-vector)`.
+    histogram_sum,
+    /// PromQL function `histogram_avg(v instant-vector)`.
+    histogram_avg,
+}
+
+/// PromQL function `vector(s scalar)`.
+pub fn vector(value: impl Into<Expr>) -> Expr {
+    function("vector", [value.into()])
+}
+
+/// PromQL function `time()`.
+pub fn time() -> Expr {
+    function("time", Vec::<FunctionArg>::new())
+}
+
+/// PromQL function `clamp(v instant-vector, min scalar, max scalar)`.
+pub fn clamp(expr: impl Into<Expr>, min: f64, max: f64) -> Expr {
+    function("clamp", [expr.into(), min.into(), max.into()])
+}
+
+/// PromQL function `clamp_min(v instant-vector, min scalar)`.
+pub fn clamp_min(expr: impl Into<Expr>, min: f64) -> Expr {
+    function("clamp_min", [expr.into(), min.into()])
+}
+
+/// PromQL function `clamp_max(v instant-vector, max scalar)`.
+pub fn clamp_max(expr: impl Into<Expr>, max: f64) -> Expr {
+    function("clamp_max", [expr.into(), max.into()])
+}
+
+/// PromQL function `histogram_quantile(φ scalar, b instant-vector)`.
+pub fn histogram_quantile(quantile: impl Into<Expr>, expr: impl Into<Expr>) -> Expr {
+    function("histogram_quantile", [quantile.into(), expr.into()])
+}
+
+/// PromQL function `histogram_fraction(lower scalar, upper scalar, b instant-vector)`.
+pub fn histogram_fraction(lower: f64, upper: f64, expr: impl Into<Expr>) -> Expr {
+    function(
+        "histogram_fraction",
+        [lower.into(), upper.into(), expr.into()],
+    )
+}
+
+/// PromQL function `quantile_over_time(φ scalar, v range-vector)`.
+pub fn quantile_over_time(quantile: impl Into<Expr>, range: RangeExpr) -> Expr {
+    function(
+        "quantile_over_time",
+        [
+            FunctionArg::Expr(quantile.into()),
+            FunctionArg::Range(range),
+        ],
+    )
+}
+
+/// PromQL function `predict_linear(v range-vector, t scalar)`.
+pub fn predict_linear(range: RangeExpr, seconds: impl Into<Expr>) -> Expr {
+    function(
+        "predict_linear",
+        [FunctionArg::Range(range), FunctionArg::Expr(seconds.into())],
+    )
+}
+
+/// PromQL function `label_replace(v instant-vector, dst_label string, replacement string, src_label string, regex string)`.
+pub fn label_replace(
+    expr: impl Into<Expr>,
+    dst_label: impl std::fmt::Display,
+    replacement: impl std::fmt::Display,
+    src_label: impl std::fmt::Display,
+    regex: impl std::fmt::Display,
+) -> Expr {
+    function(
+        "label_replace",
+        [
+            expr.into(),
+            Expr::string(dst_label),
+            Expr::string(replacement),
+            Expr::string(src_label),
+            Expr::string(regex),
+        ],
+    )
+}
+
+/// PromQL function `label_join(v instant-vector, dst_label string, separator string, src_label_1 string, ...)`.
+pub fn label_join<T>(
+    expr: impl Into<Expr>,
+    dst_label: impl std::fmt::Display,
+    separator: impl std::fmt::Display,
+    src_labels: T,
+) -> Expr
+where
+    T: IntoIterator,
+    T::Item: std::fmt::Display,
+{
+    let mut args = vec![
+        expr.into(),
+        Expr::string(dst_label),
+        Expr::string(separator),
+    ];
+    args.extend(src_labels.into_iter().map(Expr::string));
+    function("label_join", args)
+}
+
+/// An aggregation over the dimensions of an instant vector, optionally
+/// preserving (`by`) or dropping (`without`) a set of labels.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Aggregation {
+    op: &'static str,
+    param: Option<Box<Expr>>,
+    expr: Box<Expr>,
+    grouping: Option<Grouping>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Grouping {
+    By(Vec<String>),
+    Without(Vec<String>),
+}
+
+impl Aggregation {
+    fn new(op: &'static str, param: Option<Expr>, expr: Expr) -> Self {
+        Aggregation {
+            op,
+            param: param.map(Box::new),
+            expr: Box::new(expr),
+            grouping: None,
+        }
+    }
+
+    /// Preserve the given labels in the result and aggregate over all others.
+    /// Calling this repeatedly will replace the current grouping.
+    pub fn by<T>(mut self, labels: T) -> Self
+    where
+        T: IntoIterator,
+        T::Item: std::fmt::Display,
+    {
+        self.grouping = Some(Grouping::By(
+            labels.into_iter().map(|l| l.to_string()).collect(),
+        ));
+        self
+    }
+
+    /// Drop the given labels from the result and aggregate over them.
+    /// Calling this repeatedly will replace the current grouping.
+    pub fn without<T>(mut self, labels: T) -> Self
+    where
+        T: IntoIterator,
+        T::Item: std::fmt::Display,
+    {
+        self.grouping = Some(Grouping::Without(
+            labels.into_iter().map(|l| l.to_string()).collect(),
+        ));
+        self
+    }
+}
+
+impl fmt::Display for Aggregation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.op)?;
+        match &self.grouping {
+            Some(Grouping::By(labels)) => {
+                f.write_str(" by ")?;
+                write_labels(f, labels)?;
+                f.write_str(" ")?;
+            }
+            Some(Grouping::Without(labels)) => {
+                f.write_str(" without ")?;
+                write_labels(f, labels)?;
+                f.write_str(" ")?;
+            }
+            None => {}
+        }
+        f.write_str("(")?;
+        if let Some(param) = &self.param {
+            write!(f, "{}, ", param)?;
+        }
+        write!(f, "{})", self.expr)
+    }
+}
+
+macro_rules! aggregations {
+    ($($(#[$doc:meta])* $name:ident),* $(,)?) => {
+        $(
+            $(#[$doc])*
+            pub fn $name(expr: impl Into<Expr>) -> Aggregation {
+                Aggregation::new(stringify!($name), None, expr.into())
+            }
+        )*
+    };
+}
+
+aggregations! {
+    /// PromQL aggregation operator `sum`.
+    sum,
+    /// PromQL aggregation operator `min`.
+    min,
+    /// PromQL aggregation operator `max`.
+    max,
+    /// PromQL aggregation operator `avg`.
+    avg,
+    /// PromQL aggregation operator `group`.
+    group,
+    /// PromQL aggregation operator `stddev`.
+    stddev,
+    /// PromQL aggregation operator `stdvar`.
+    stdvar,
+    /// PromQL aggregation operator `count`.
+    count,
+}
+
+/// PromQL aggregation operator `topk`.
+pub fn topk(k: impl Into<Expr>, expr: impl Into<Expr>) -> Aggregation {
+    Aggregation::new("topk", Some(k.into()), expr.into())
+}
+
+/// PromQL aggregation operator `bottomk`.
+pub fn bottomk(k: impl Into<Expr>, expr: impl Into<Expr>) -> Aggregation {
+    Aggregation::new("bottomk", Some(k.into()), expr.into())
+}
+
+/// PromQL aggregation operator `quantile`.
+pub fn quantile(quantile: impl Into<Expr>, expr: impl Into<Expr>) -> Aggregation {
+    Aggregation::new("quantile", Some(quantile.into()), expr.into())
+}
+
+/// PromQL aggregation operator `count_values`.
+pub fn count_values(label: impl std::fmt::Display, expr: impl Into<Expr>) -> Aggregation {
+    Aggregation::new("count_values", Some(Expr::string(label)), expr.into())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Pow,
+    Atan2,
+    Equal,
+    NotEqual,
+    Greater,
+    GreaterEqual,
+    Less,
+    LessEqual,
+    And,
+    Or,
+    Unless,
+}
+
+impl BinaryOp {
+    fn is_comparison(&self) -> bool {
+        matches!(
+            self,
+            Self::Equal
+                | Self::NotEqual
+                | Self::Greater
+                | Self::GreaterEqual
+                | Self::Less
+                | Self::LessEqual
+        )
+    }
+}
+
+impl fmt::Display for BinaryOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Add => f.write_str("+"),
+            Self::Sub => f.write_str("-"),
+            Self::Mul => f.write_str("*"),
+            Self::Div => f.write_str("/"),
+            Self::Mod => f.write_str("%"),
+            Self::Pow => f.write_str("^"),
+            Self::Atan2 => f.write_str("atan2"),
+            Self::Equal => f.write_str("=="),
+            Self::NotEqual => f.write_str("!="),
+            Self::Greater => f.write_str(">"),
+            Self::GreaterEqual => f.write_str(">="),
+            Self::Less => f.write_str("<"),
+            Self::LessEqual => f.write_str("<="),
+            Self::And => f.write_str("and"),
+            Self::Or => f.write_str("or"),
+            Self::Unless => f.write_str("unless"),
+        }
+    }
+}
+
+/// A binary expression, optionally modified by vector matching clauses
+/// (`on`/`ignoring`, `group_left`/`group_right`) or the `bool` modifier.
+///
+/// ```rust
+/// use prometheus_http_query::expr::{Expr, VectorSelector};
+/// use prometheus_http_query::Selector;
+///
+/// let lhs = Expr::from(Selector::new().metric("method_code:http_errors:rate5m"));
+/// let rhs = Expr::from(Selector::new().metric("method:http_requests:rate5m"));
+///
+/// let q = (lhs / rhs).ignoring(["code"]).group_left(Vec::<String>::new());
+///
+/// assert_eq!(
+///     q.to_string(),
+///     r#"{__name__="method_code:http_errors:rate5m"} / ignoring (code) group_left () {__name__="method:http_requests:rate5m"}"#
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct BinaryExpr {
+    lhs: Box<Expr>,
+    op: BinaryOp,
+    rhs: Box<Expr>,
+    return_bool: bool,
+    matching: Option<Matching>,
+    group: Option<Group>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Matching {
+    On(Vec<String>),
+    Ignoring(Vec<String>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Group {
+    Left(Vec<String>),
+    Right(Vec<String>),
+}
+
+impl BinaryExpr {
+    fn new(lhs: Expr, op: BinaryOp, rhs: Expr) -> Self {
+        BinaryExpr {
+            lhs: Box::new(lhs),
+            op,
+            rhs: Box::new(rhs),
+            return_bool: false,
+            matching: None,
+            group: None,
+        }
+    }
+
+    /// Only match samples of both sides that have the same values for the given labels.
+    /// Calling this repeatedly will replace the current matching clause.
+    pub fn on<T>(mut self, labels: T) -> Self
+    where
+        T: IntoIterator,
+        T::Item: std::fmt::Display,
+    {
+        self.matching = Some(Matching::On(
+            labels.into_iter().map(|l| l.to_string()).collect(),
+        ));
+        self
+    }
+
+    /// Ignore the given labels when matching samples of both sides.
+    /// Calling this repeatedly will replace the current matching clause.
+    pub fn ignoring<T>(mut self, labels: T) -> Self
+    where
+        T: IntoIterator,
+        T::Item: std::fmt::Display,
+    {
+        self.matching = Some(Matching::Ignoring(
+            labels.into_iter().map(|l| l.to_string()).collect(),
+        ));
+        self
+    }
+
+    /// Allow many-to-one matching where the left side has the higher cardinality,
+    /// optionally copying the given labels from the right side. Without a matching clause
+    /// all labels are matched, i.e. `ignoring ()` is rendered.
+    /// Calling this repeatedly will replace the current grouping modifier.
+    pub fn group_left<T>(mut self, labels: T) -> Self
+    where
+        T: IntoIterator,
+        T::Item: std::fmt::Display,
+    {
+        self.group = Some(Group::Left(
+            labels.into_iter().map(|l| l.to_string()).collect(),
+        ));
+        self
+    }
+
+    /// Allow one-to-many matching where the right side has the higher cardinality,
+    /// optionally copying the given labels from the left side. Without a matching clause
+    /// all labels are matched, i.e. `ignoring ()` is rendered.
+    /// Calling this repeatedly will replace the current grouping modifier.
+    pub fn group_right<T>(mut self, labels: T) -> Self
+    where
+        T: IntoIterator,
+        T::Item: std::fmt::Display,
+    {
+        self.group = Some(Group::Right(
+            labels.into_iter().map(|l| l.to_string()).collect(),
+        ));
+        self
+    }
+
+    /// Return 0 or 1 instead of filtering samples when using a comparison operator (`bool` modifier).
+    /// This has no effect on other operators.
+    pub fn return_bool(mut self) -> Self {
+        self.return_bool = true;
+        self
+    }
+}
+
+impl fmt::Display for BinaryExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.lhs.fmt_operand(f)?;
+        write!(f, " {}", self.op)?;
+        if self.return_bool && self.op.is_comparison() {
+            f.write_str(" bool")?;
+        }
+        match &self.matching {
+            Some(Matching::On(labels)) => {
+                f.write_str(" on ")?;
+                write_labels(f, labels)?;
+            }
+            Some(Matching::Ignoring(labels)) => {
+                f.write_str(" ignoring ")?;
+                write_labels(f, labels)?;
+            }
+            // PromQL only allows grouping modifiers after a matching clause. Ignoring no
+            // labels is the same as the default matching behavior.
+            None if self.group.is_some() => f.write_str(" ignoring ()")?,
+            None => {}
+        }
+        match &self.group {
+            Some(Group::Left(labels)) => {
+                f.write_str(" group_left ")?;
+                write_labels(f, labels)?;
+            }
+            Some(Group::Right(labels)) => {
+                f.write_str(" group_right ")?;
+                write_labels(f, labels)?;
+            }
+            None => {}
+        }
+        f.write_str(" ")?;
+        self.rhs.fmt_operand(f)
+    }
+}
+
+macro_rules! binary_operators {
+    ($($ty:ty),*) => {
+        $(
+            impl<T: Into<Expr>> Add<T> for $ty {
+                type Output = BinaryExpr;
+
+                fn add(self, rhs: T) -> BinaryExpr {
+                    BinaryExpr::new(self.into(), BinaryOp::Add, rhs.into())
+                }
+            }
+
+            impl<T: Into<Expr>> Sub<T> for $ty {
+                type Output = BinaryExpr;
+
+                fn sub(self, rhs: T) -> BinaryExpr {
+                    BinaryExpr::new(self.into(), BinaryOp::Sub, rhs.into())
+                }
+            }
+
+            impl<T: Into<Expr>> Mul<T> for $ty {
+                type Output = BinaryExpr;
+
+                fn mul(self, rhs: T) -> BinaryExpr {
+                    BinaryExpr::new(self.into(), BinaryOp::Mul, rhs.into())
+                }
+            }
+
+            impl<T: Into<Expr>> Div<T> for $ty {
+                type Output = BinaryExpr;
+
+                fn div(self, rhs: T) -> BinaryExpr {
+                    BinaryExpr::new(self.into(), BinaryOp::Div, rhs.into())
+                }
+            }
+
+            impl<T: Into<Expr>> Rem<T> for $ty {
+                type Output = BinaryExpr;
+
+                fn rem(self, rhs: T) -> BinaryExpr {
+                    BinaryExpr::new(self.into(), BinaryOp::Mod, rhs.into())
+                }
+            }
+
+            impl Neg for $ty {
+                type Output = Expr;
+
+                fn neg(self) -> Expr {
+                    Expr {
+                        kind: ExprKind::Negation(Box::new(self.into())),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+binary_operators!(Expr, VectorSelector, Aggregation, BinaryExpr);
+
+// Numbers are formatted the way the PromQL parser expects them, e.g. "+Inf" instead of "inf".
+fn write_number(f: &mut fmt::Formatter<'_>, n: f64) -> fmt::Result {
+    if n.is_nan() {
+        f.write_str("NaN")
+    } else if n == f64::INFINITY {
+        f.write_str("+Inf")
+    } else if n == f64::NEG_INFINITY {
+        f.write_str("-Inf")
+    } else {
+        write!(f, "{}", n)
+    }
+}
+
+fn write_labels(f: &mut fmt::Formatter<'_>, labels: &[String]) -> fmt::Result {
+    f.write_str("(")?;
+    for (i, label) in labels.iter().enumerate() {
+        if i > 0 {
+            f.write_str(", ")?;
+        }
+        write_label_name(f, label)?;
+    }
+    f.write_str(")")
+}
+
+// Durations are formatted like Prometheus does (see `PrometheusDuration`), except that
+// offsets may be negative.
+fn write_duration(f: &mut fmt::Formatter<'_>, d: Duration) -> fmt::Result {
+    if d.is_negative() {
+        f.write_str("-")?;
+    }
+    write_millis(f, d.whole_milliseconds().unsigned_abs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metric(name: &str) -> VectorSelector {
+        VectorSelector::from(Selector::new().metric(name))
+    }
+
+    #[test]
+    fn test_selector_modifiers() {
+        let s = metric("up").offset(Duration::minutes(5)).at(1609746000);
+        assert_eq!(s.to_string(), r#"{__name__="up"} offset 5m @ 1609746000"#);
+
+        let s = metric("up").offset(Duration::seconds(-90)).at(At::End);
+        assert_eq!(s.to_string(), r#"{__name__="up"} offset -1m30s @ end()"#);
+
+        let r = metric("up")
+            .range(Duration::days(14))
+            .offset(Duration::weeks(1))
+            .at(At::Start);
+        assert_eq!(r.to_string(), r#"{__name__="up"}[2w] offset 1w @ start()"#);
+
+        let r = metric("up").range(Duration::milliseconds(90_500));
+        assert_eq!(r.to_string(), r#"{__name__="up"}[1m30s500ms]"#);
+
+        let r = metric("up").range("1h30m".parse::<crate::PrometheusDuration>().unwrap());
+        assert_eq!(r.to_string(), r#"{__name__="up"}[1h30m]"#);
+    }
+
+    #[test]
+    fn test_functions_and_aggregations() {
+        let q = sum(rate(
+            metric("http_requests_total").range(Duration::minutes(5)),
+        ))
+        .by(["job", "code"]);
+        assert_eq!(
+            q.to_string(),
+            r#"sum by (job, code) (rate({__name__="http_requests_total"}[5m]))"#
+        );
+
+        let q = topk(5.0, metric("up")).without(["instance"]);
+        assert_eq!(
+            q.to_string(),
+            r#"topk without (instance) (5, {__name__="up"})"#
+        );
+
+        let q = count_values("version", metric("build_info"));
+        assert_eq!(
+            q.to_string(),
+            r#"count_values("version", {__name__="build_info"})"#
+        );
+
+        let q = max_over_time(
+            rate(metric("x").range(Duration::minutes(1)))
+                .subquery(Duration::minutes(30), Some(Duration::minutes(1))),
+        );
+        assert_eq!(
+            q.to_string(),
+            r#"max_over_time(rate({__name__="x"}[1m])[30m:1m])"#
+        );
+
+        let q = label_replace(metric("up"), "host", "$1", "instance", "(.*):.*");
+        assert_eq!(
+            q.to_string(),
+            r#"label_replace({__name__="up"}, "host", "$1", "instance", "(.*):.*")"#
+        );
+
+        assert_eq!(time().to_string(), "time()");
+        assert_eq!(
+            clamp(metric("x"), f64::NEG_INFINITY, 100.0).to_string(),
+            r#"clamp({__name__="x"}, -Inf, 100)"#
+        );
+    }
+
+    #[test]
+    fn test_binary_expressions() {
+        let q = (metric("a") + metric("b")) * 2.0;
+        assert_eq!(q.to_string(), r#"({__name__="a"} + {__name__="b"}) * 2"#);
+
+        let q = Expr::from(metric("a")).greater_than(0.5).return_bool();
+        assert_eq!(q.to_string(), r#"{__name__="a"} > bool 0.5"#);
+
+        let q = (metric("a") / metric("b")).on(["job"]).group_left(["team"]);
+        assert_eq!(
+            q.to_string(),
+            r#"{__name__="a"} / on (job) group_left (team) {__name__="b"}"#
+        );
+
+        let q = (metric("a") * metric("b")).group_right(Vec::<String>::new());
+        assert_eq!(
+            q.to_string(),
+            r#"{__name__="a"} * ignoring () group_right () {__name__="b"}"#
+        );
+
+        let q = Expr::from(metric("a"))
+            .unless(metric("b"))
+            .ignoring(["instance"]);
+        assert_eq!(
+            q.to_string(),
+            r#"{__name__="a"} unless ignoring (instance) {__name__="b"}"#
+        );
+
+        let q = -(metric("a") - metric("b"));
+        assert_eq!(q.to_string(), r#"-({__name__="a"} - {__name__="b"})"#);
+
+        let q = Expr::from(metric("a")).and(metric("b")).return_bool();
+        assert_eq!(q.to_string(), r#"{__name__="a"} and {__name__="b"}"#);
+    }
+
+    #[test]
+    fn test_quoted_label_names() {
+        let q = sum(metric("up")).by(["job", "service.name"]);
+        assert_eq!(
+            q.to_string(),
+            r#"sum by (job, "service.name") ({__name__="up"})"#
+        );
+    }
+
+    #[test]
+    fn test_string_escaping() {
+        let q = Expr::string("a \"quoted\" \\ value\n");
+        assert_eq!(q.to_string(), r#""a \"quoted\" \\ value\n""#);
+    }
+}
@@ -49,9 +49,8 @@
 //!     let response = client.query(q).get().await?;
 //!     let result = response.data().as_vector().expect("Expected result of type vector");
 //!
-//!     if !result.is_empty() {
-//!         let first = result.first().unwrap();
-//!         println!("Received a total of {} HTTP requests", first.sample().value());
+//!     if let Some(sample) = result.first().and_then(|v| v.sample()) {
+//!         println!("Received a total of {} HTTP requests", sample.value());
 //!     }
 //!
 //!     // HTTP POST is also supported.
@@ -141,7 +140,7 @@
 //!
 //! # Features
 //!
-//! At this point all available feature flags pertain to the [`Client`]s TLS configuration. They enable feature flags of
+//! Apart from `aws-sigv4` and `blocking` all available feature flags pertain to the [`Client`]s TLS configuration. They enable feature flags of
 //! the `reqwest` crate by the same name.<br>
 //! See the [reqwest documentation](https://docs.rs/reqwest/0.11.14/reqwest/index.html#optional-features) for details on
 //! these feature flags.<br>
@@ -150,6 +149,12 @@
 //!
 //! `prometheus-http-query = { version = "0.7", default-features = false, features = ["rustls-tls"] }`
 //!
+//! The `aws-sigv4` feature enables AWS Signature Version 4 request signing (see `SigV4` and `Auth::SigV4`),
+//! e.g. in order to query Amazon Managed Service for Prometheus.
+//!
+//! The `blocking` feature enables a synchronous client in the `blocking` module that mirrors the [`Client`] and
+//! its query builders for use outside of an asynchronous runtime.
+//!
 //! # Compatibility
 //!
 //! The crate is generally compatible with Prometheus server >=2.30. However individual [`Client`] methods might
@@ -166,18 +171,22 @@
 //! may fail at different stages. In general the following approach is taken to return the most significant
 //! error to the caller:
 //! - When the server's response contains header `Content-Type: application/json` (or variants thereof) the
-//! JSON body is parsed to the target type, regardless of the HTTP status code, since Prometheus returns elaborate
-//! error messages within the HTTP body in any case.
-//! A JSON response having `"status": "success"` is deserialized to the target type of this function and returned
-//! within `Result::Ok`. A response with `"status": "error"` is instead deserialized to a [`error::PrometheusError`]
-//! and returned within `Result::Err`.
+//!   JSON body is parsed to the target type, regardless of the HTTP status code, since Prometheus returns elaborate
+//!   error messages within the HTTP body in any case.
+//!   A JSON response having `"status": "success"` is deserialized to the target type of this function and returned
+//!   within `Result::Ok`. A response with `"status": "error"` is instead deserialized to a [`error::PrometheusError`]
+//!   and returned within `Result::Err`.
 //! - Any other server HTTP 4xx/5xx responses without the proper header indicating a JSON-encoded body are
-//! returned as [`Error::Client`] within `Result::Err`. For example, this may happen when an intermediate proxy server
-//! fails to handle a request and subsequently return a plain text error message and a non-2xx HTTP status code.
+//!   returned as [`Error::Client`] within `Result::Err`. For example, this may happen when an intermediate proxy server
+//!   fails to handle a request and subsequently return a plain text error message and a non-2xx HTTP status code.
 //!
 //! # Supported operations
 //!
-//! - [x] Execute instant and range queries (GET or POST) and properly parse the results (vector/matrix/scalar)
+//! - [x] Execute instant and range queries (GET or POST) and properly parse the results (vector/matrix/scalar/string)
+//! - [x] Stream the series of huge query results while they are parsed (see [`SeriesStream`])
+//! - [x] Execute exemplar queries
+//! - [x] Format and parse PromQL queries
+//! - [x] Build PromQL queries programmatically (see [`expr`])
 //! - [x] Execute series metadata queries
 //! - [x] Execute label metadata queries (names/values)
 //! - [x] Retrieve target discovery status
@@ -191,27 +200,52 @@
 //! - [x] Prometheus server flags
 //! - [x] Prometheus server build information
 //! - [x] Prometheus server runtime information
-//! - [ ] Prometheus server config
+//! - [x] TSDB admin APIs (snapshot, delete series, clean tombstones)
+//! - [x] Prometheus server config
+//! - [x] Query several Prometheus servers with failover or result merging (see [`MultiClient`] and [`FanOut`])
 //!
 //! # Limitations
 //!
 //! * Some [`Client`] methods may not work with older versions of the Prometheus server.
-//! * The [String](https://prometheus.io/docs/prometheus/latest/querying/api/#strings) result type is not supported
-//! as it is currently not used by Prometheus.
-//! * Warnings contained in an API response will be ignored.
+mod arg;
+pub mod ast;
+mod auth;
+#[cfg(feature = "blocking")]
+pub mod blocking;
 mod client;
 mod direct;
+mod duration;
 pub mod error;
+pub mod expr;
+mod middleware;
+mod multi;
 pub mod response;
+mod retry;
 mod selector;
+#[cfg(feature = "aws-sigv4")]
+mod sigv4;
+mod stream;
+#[cfg(test)]
+mod test_util;
+mod transport;
 mod util;
+pub use self::arg::{QueryDuration, Timestamp, ToQueryDuration, ToTimestamp};
+pub use self::auth::{Auth, OAuth2};
 pub use self::client::{
-    Client, InstantQueryBuilder, LabelNamesQueryBuilder, LabelValuesQueryBuilder,
-    MetricMetadataQueryBuilder, RangeQueryBuilder, RulesQueryBuilder, SeriesQueryBuilder,
-    TargetMetadataQueryBuilder,
+    Client, ClientBuilder, DeleteSeriesQueryBuilder, ExemplarsQueryBuilder, InstantQueryBuilder,
+    LabelNamesQueryBuilder, LabelValuesQueryBuilder, MetricMetadataQueryBuilder, RangeQueryBuilder,
+    RulesQueryBuilder, SeriesQueryBuilder, TargetMetadataQueryBuilder,
 };
 pub use self::direct::*;
+pub use self::duration::PrometheusDuration;
 pub use self::error::Error;
+pub use self::middleware::{Middleware, MiddlewareRequest, MiddlewareResponse};
+pub use self::multi::{EndpointStrategy, FanOut, FanOutResult, MultiClient};
+pub use self::retry::RetryPolicy;
 pub use self::selector::Selector;
+#[cfg(feature = "aws-sigv4")]
+pub use self::sigv4::SigV4;
+pub use self::stream::SeriesStream;
+pub use self::transport::{HttpRequest, HttpResponse, Transport, TransportFuture};
 pub use self::util::RuleKind;
 pub use self::util::TargetState;
@@ -0,0 +1,128 @@
+use crate::error::Error;
+use crate::transport::HttpResponse;
+use reqwest::header::{HeaderMap, HeaderValue, IntoHeaderName};
+use reqwest::{Method, StatusCode};
+use std::time::Duration;
+
+/// A hook that is invoked for every API request sent by a [`Client`](crate::Client),
+/// see [`ClientBuilder::middleware`](crate::ClientBuilder::middleware).
+///
+/// Middleware is executed in the order it was added to the [`Client`](crate::Client): [`Middleware::before`]
+/// is called before the request is sent and may modify it or answer it instead of the server.
+/// [`Middleware::after`] is called in reverse order once the response has been received (or the request
+/// failed), including the time it took. Retries and credential refreshes happen in between, i.e. the hooks
+/// are called once per API request.
+///
+/// ```rust
+/// use prometheus_http_query::{Client, Error, Middleware, MiddlewareRequest, MiddlewareResponse};
+/// use prometheus_http_query::HttpResponse;
+/// use reqwest::header::HeaderValue;
+///
+/// struct Tracing;
+///
+/// impl Middleware for Tracing {
+///     fn before(&self, request: &mut MiddlewareRequest) -> Option<HttpResponse> {
+///         request.header("traceparent", HeaderValue::from_static("00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01"));
+///         None
+///     }
+///
+///     fn after(&self, request: &MiddlewareRequest, response: Result<&MiddlewareResponse, &Error>) {
+///         match response {
+///             Ok(r) => println!("{} {} {} ({:?})", request.method(), request.path(), r.status(), r.elapsed()),
+///             Err(e) => eprintln!("{} {} failed: {}", request.method(), request.path(), e),
+///         }
+///     }
+/// }
+///
+/// fn main() -> Result<(), anyhow::Error> {
+///     let client = Client::builder().middleware(Tracing).build()?;
+///     Ok(())
+/// }
+/// ```
+pub trait Middleware: Send + Sync {
+    /// Inspect or modify the request before it is sent. Returning a response short-circuits
+    /// the request, i.e. it is not sent to the server and the remaining middleware is skipped.
+    fn before(&self, request: &mut MiddlewareRequest) -> Option<HttpResponse> {
+        let _ = request;
+        None
+    }
+
+    /// Inspect the response (or the error) after the request has been sent.
+    fn after(&self, request: &MiddlewareRequest, response: Result<&MiddlewareResponse, &Error>) {
+        let _ = (request, response);
+    }
+}
+
+/// An API request as seen by [`Middleware`].
+#[derive(Debug, Clone)]
+pub struct MiddlewareRequest {
+    pub(crate) method: Method,
+    pub(crate) path: String,
+    pub(crate) params: Vec<(String, String)>,
+    pub(crate) headers: HeaderMap<HeaderValue>,
+}
+
+impl MiddlewareRequest {
+    /// Return the HTTP method.
+    pub fn method(&self) -> &Method {
+        &self.method
+    }
+
+    /// Return the path of the API endpoint relative to the base URL of the
+    /// [`Client`](crate::Client), e.g. `api/v1/query`.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Return the request parameters that are sent either as query string or form body.
+    pub fn params(&self) -> &[(String, String)] {
+        &self.params
+    }
+
+    /// Return a mutable reference to the request parameters.
+    pub fn params_mut(&mut self) -> &mut Vec<(String, String)> {
+        &mut self.params
+    }
+
+    /// Return the headers of this request. Note that these do not include the default headers
+    /// and credentials configured for the [`Client`](crate::Client), which take lower precedence.
+    pub fn headers(&self) -> &HeaderMap<HeaderValue> {
+        &self.headers
+    }
+
+    /// Return a mutable reference to the headers of this request.
+    pub fn headers_mut(&mut self) -> &mut HeaderMap<HeaderValue> {
+        &mut self.headers
+    }
+
+    /// Include an additional header in the request, replacing a header of the same name.
+    pub fn header<K: IntoHeaderName, T: Into<HeaderValue>>(&mut self, name: K, value: T) {
+        self.headers.insert(name, value.into());
+    }
+}
+
+/// A response as seen by [`Middleware::after`].
+#[derive(Debug, Clone)]
+pub struct MiddlewareResponse {
+    pub(crate) status: StatusCode,
+    pub(crate) headers: HeaderMap<HeaderValue>,
+    pub(crate) elapsed: Duration,
+}
+
+impl MiddlewareResponse {
+    /// Return the status code.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// Return the response headers.
+    pub fn headers(&self) -> &HeaderMap<HeaderValue> {
+        &self.headers
+    }
+
+    /// Return the time it took from sending the request until the response headers were received,
+    /// including retries.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+}
@@ -0,0 +1,787 @@
+use crate::arg::{ToQueryDuration, ToTimestamp};
+use crate::client::Client;
+use crate::error::Error;
+use crate::response::{
+    Annotations, Data, HistogramSample, InstantVector, PromqlResult, RangeVector, Sample,
+};
+use crate::retry::{is_connect, is_timeout};
+use crate::util::ToBaseUrl;
+use futures_util::future::join_all;
+use reqwest::StatusCode;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use url::Url;
+
+/// Determines how a [`MultiClient`] distributes requests among its endpoints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EndpointStrategy {
+    /// Send requests to the first healthy endpoint and fail over to the next one
+    /// if the request fails.
+    Failover,
+    /// Distribute requests evenly among healthy endpoints and fail over to the next
+    /// one if the request fails.
+    RoundRobin,
+    /// Send PromQL queries to all endpoints concurrently and merge the results,
+    /// deduplicating series by their label sets. Errors of single endpoints are reported
+    /// as warnings of the merged result. Other requests are handled like
+    /// [`EndpointStrategy::Failover`].
+    QueryAll,
+}
+
+/// A client that sends requests to several Prometheus servers, e.g. the replicas of
+/// a highly available Prometheus setup.
+///
+/// Endpoints are marked unhealthy when a request fails due to a network error or because the server is
+/// unavailable, and healthy when a request succeeds. Unhealthy endpoints are only tried after all healthy
+/// endpoints failed. Use [`MultiClient::check_health`] to update the health of all endpoints using the
+/// readiness endpoint of each server.
+///
+/// ```rust
+/// use prometheus_http_query::{Client, EndpointStrategy, MultiClient};
+///
+/// #[tokio::main(flavor = "current_thread")]
+/// async fn main() -> Result<(), anyhow::Error> {
+///     let client = MultiClient::new(
+///         Client::default(),
+///         ["http://prometheus-0:9090", "http://prometheus-1:9090"],
+///         EndpointStrategy::Failover,
+///     )?;
+///
+///     client.check_health().await;
+///
+///     let response = client.query("up").await;
+///
+///     // Execute any other request with failover.
+///     let response = client
+///         .execute(|c| async move { c.query("up").at(1648373100).get().await })
+///         .await;
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone)]
+pub struct MultiClient {
+    endpoints: Arc<Vec<Endpoint>>,
+    strategy: EndpointStrategy,
+    next: Arc<AtomicUsize>,
+}
+
+struct Endpoint {
+    client: Client,
+    healthy: AtomicBool,
+}
+
+impl MultiClient {
+    /// Create a client that sends requests to the given base URLs. All settings of the given
+    /// [`Client`] (e.g. default headers and credentials) apply to each endpoint, apart from
+    /// its base URL. Fails if no URL is given or any URL is invalid.
+    pub fn new<T>(client: Client, urls: T, strategy: EndpointStrategy) -> Result<Self, Error>
+    where
+        T: IntoIterator,
+        T::Item: AsRef<str>,
+    {
+        let endpoints = urls
+            .into_iter()
+            .map(|url| {
+                let mut client = client.clone();
+                client.base_url = url.as_ref().to_base_url()?;
+                Ok(Endpoint {
+                    client,
+                    healthy: AtomicBool::new(true),
+                })
+            })
+            .collect::<Result<Vec<Endpoint>, Error>>()?;
+
+        if endpoints.is_empty() {
+            return Err(Error::EmptyEndpoints);
+        }
+
+        Ok(MultiClient {
+            endpoints: Arc::new(endpoints),
+            strategy,
+            next: Default::default(),
+        })
+    }
+
+    /// Return the base URL of each endpoint along with its current health.
+    pub fn endpoints(&self) -> Vec<(&Url, bool)> {
+        self.endpoints
+            .iter()
+            .map(|e| (e.client.base_url(), e.healthy.load(Ordering::Relaxed)))
+            .collect()
+    }
+
+    /// Return the [`Client`] of each endpoint.
+    pub fn clients(&self) -> impl Iterator<Item = &Client> {
+        self.endpoints.iter().map(|e| &e.client)
+    }
+
+    /// Check the readiness of all endpoints concurrently and mark them healthy or
+    /// unhealthy accordingly.
+    pub async fn check_health(&self) {
+        let checks = self.endpoints.iter().map(|e| async move {
+            let ready = e.client.is_server_ready().await.unwrap_or(false);
+            e.healthy.store(ready, Ordering::Relaxed);
+        });
+        join_all(checks).await;
+    }
+
+    /// Execute an instant query using the configured [`EndpointStrategy`].
+    pub async fn query(&self, query: impl std::fmt::Display) -> Result<PromqlResult, Error> {
+        let query = query.to_string();
+        self.query_with(|c| {
+            let query = query.clone();
+            async move { c.query(query).get().await }
+        })
+        .await
+    }
+
+    /// Execute a range query using the configured [`EndpointStrategy`].
+    /// See [`Client::query_range`] for a description of the arguments.
+    pub async fn query_range(
+        &self,
+        query: impl std::fmt::Display,
+        start: impl ToTimestamp,
+        end: impl ToTimestamp,
+        step: impl ToQueryDuration,
+    ) -> Result<PromqlResult, Error> {
+        let query = query.to_string();
+        let (start, end, step) = (
+            start.to_timestamp(),
+            end.to_timestamp(),
+            step.to_query_duration(),
+        );
+        self.query_with(|c| {
+            let (query, start, end, step) =
+                (query.clone(), start.clone(), end.clone(), step.clone());
+            async move { c.query_range(query, start, end, step).get().await }
+        })
+        .await
+    }
+
+    /// Execute an arbitrary PromQL query using the configured [`EndpointStrategy`], i.e. the query
+    /// is sent to all endpoints and the results are merged when using [`EndpointStrategy::QueryAll`].
+    ///
+    /// A query using [`EndpointStrategy::QueryAll`] only fails if it fails on all endpoints. Otherwise the
+    /// errors of the failed endpoints are reported as warnings of the merged result prefixed with the base URL
+    /// of the endpoint, see [`PromqlResult::warnings`]. Use [`FanOut`] in order to handle these errors individually.
+    pub async fn query_with<F, Fut>(&self, f: F) -> Result<PromqlResult, Error>
+    where
+        F: Fn(Client) -> Fut,
+        Fut: Future<Output = Result<PromqlResult, Error>>,
+    {
+        if self.strategy != EndpointStrategy::QueryAll {
+            return self.execute(f).await;
+        }
+
+        let requests = self.endpoints.iter().map(|e| {
+            let response = f(e.client.clone());
+            async move {
+                let result = response.await;
+                e.update_health(&result);
+                (e.client.base_url(), result)
+            }
+        });
+
+        let mut results = vec![];
+        let mut errors = vec![];
+
+        for (url, result) in join_all(requests).await {
+            match result {
+                Ok(r) => results.push(r),
+                Err(e) => errors.push((url, e)),
+            }
+        }
+
+        if results.is_empty() && !errors.is_empty() {
+            return Err(errors.remove(0).1);
+        }
+
+        let mut merged = merge_results(results);
+        merged
+            .annotations
+            .warnings
+            .extend(errors.into_iter().map(|(url, e)| format!("{}: {}", url, e)));
+        Ok(merged)
+    }
+
+    /// Execute an arbitrary request, trying one endpoint after the other according to
+    /// the configured [`EndpointStrategy`] until a request succeeds. Errors that are not caused by
+    /// an unavailable server (e.g. an invalid PromQL query) are returned immediately.
+    pub async fn execute<F, Fut, T>(&self, f: F) -> Result<T, Error>
+    where
+        F: Fn(Client) -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        let mut last_error = None;
+
+        for endpoint in self.order() {
+            let result = f(endpoint.client.clone()).await;
+            endpoint.update_health(&result);
+            match result {
+                Err(e) if is_unavailable(&e) => last_error = Some(e),
+                result => return result,
+            }
+        }
+
+        Err(last_error.unwrap_or(Error::EmptyEndpoints))
+    }
+
+    // The order in which endpoints are tried: healthy endpoints first, starting at the
+    // next endpoint in turn when using round-robin.
+    fn order(&self) -> Vec<&Endpoint> {
+        let len = self.endpoints.len();
+        let start = match self.strategy {
+            EndpointStrategy::RoundRobin => self.next.fetch_add(1, Ordering::Relaxed) % len,
+            _ => 0,
+        };
+
+        let mut endpoints: Vec<&Endpoint> = (0..len)
+            .map(|i| &self.endpoints[(start + i) % len])
+            .collect();
+        endpoints.sort_by_key(|e| !e.healthy.load(Ordering::Relaxed));
+        endpoints
+    }
+}
+
+impl Endpoint {
+    fn update_health<T>(&self, result: &Result<T, Error>) {
+        match result {
+            Ok(_) => self.healthy.store(true, Ordering::Relaxed),
+            Err(e) if is_unavailable(e) => self.healthy.store(false, Ordering::Relaxed),
+            Err(_) => {}
+        }
+    }
+}
+
+// Only network errors, timeouts and responses that indicate an overloaded or unavailable
+// server (e.g. from a proxy in front of Prometheus) cause a failover.
+fn is_unavailable(error: &Error) -> bool {
+    match error {
+        Error::Client(e) => e
+            .reqwest_error()
+            .and_then(|e| e.status())
+            .is_some_and(|s| s.is_server_error() || s == StatusCode::TOO_MANY_REQUESTS),
+        Error::Transport(e) => is_connect(e.inner()) || is_timeout(e.inner()),
+        Error::Prometheus(e) => e.is_unavailable(),
+        _ => false,
+    }
+}
+
+/// Executes the same PromQL query concurrently against several independent Prometheus servers
+/// (e.g. one per region) and merges the results into a global view.
+///
+/// Each series is labeled with the name of the server it originates from using a configurable source
+/// label, overriding a label of the same name that may already be present. Scalar and string results
+/// cannot be labeled and are taken from the first server that responded successfully.
+///
+/// Servers that fail do not fail the whole query. Instead their errors are returned alongside the merged
+/// result, see [`FanOutResult`].
+///
+/// ```rust
+/// use prometheus_http_query::{Client, FanOut};
+///
+/// #[tokio::main(flavor = "current_thread")]
+/// async fn main() -> Result<(), anyhow::Error> {
+///     let fan_out = FanOut::new("region")
+///         .client("eu", Client::try_from("http://prometheus.eu.example.com:9090")?)
+///         .client("us", Client::try_from("http://prometheus.us.example.com:9090")?);
+///
+///     let response = fan_out.query("sum(up)").await;
+///
+///     for (region, error) in response.errors() {
+///         eprintln!("failed to query {}: {}", region, error);
+///     }
+///
+///     let result = response.result();
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone)]
+pub struct FanOut {
+    label: String,
+    clients: Vec<(String, Client)>,
+}
+
+impl FanOut {
+    /// Create a new fan-out without any servers that labels each series with the given source label.
+    pub fn new(label: impl Into<String>) -> Self {
+        FanOut {
+            label: label.into(),
+            clients: vec![],
+        }
+    }
+
+    /// Add a server. The series of its results are labeled with the given value of the source label.
+    pub fn client(mut self, source: impl Into<String>, client: Client) -> Self {
+        self.clients.push((source.into(), client));
+        self
+    }
+
+    /// Return the source label.
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Return the servers along with their value of the source label.
+    pub fn clients(&self) -> &[(String, Client)] {
+        &self.clients
+    }
+
+    /// Execute an instant query against all servers.
+    pub async fn query(&self, query: impl std::fmt::Display) -> FanOutResult {
+        let query = query.to_string();
+        self.query_with(|c| {
+            let query = query.clone();
+            async move { c.query(query).get().await }
+        })
+        .await
+    }
+
+    /// Execute a range query against all servers.
+    /// See [`Client::query_range`] for a description of the arguments.
+    pub async fn query_range(
+        &self,
+        query: impl std::fmt::Display,
+        start: impl ToTimestamp,
+        end: impl ToTimestamp,
+        step: impl ToQueryDuration,
+    ) -> FanOutResult {
+        let query = query.to_string();
+        let (start, end, step) = (
+            start.to_timestamp(),
+            end.to_timestamp(),
+            step.to_query_duration(),
+        );
+        self.query_with(|c| {
+            let (query, start, end, step) =
+                (query.clone(), start.clone(), end.clone(), step.clone());
+            async move { c.query_range(query, start, end, step).get().await }
+        })
+        .await
+    }
+
+    /// Execute an arbitrary PromQL query against all servers, e.g. in order to use
+    /// additional query parameters.
+    pub async fn query_with<F, Fut>(&self, f: F) -> FanOutResult
+    where
+        F: Fn(Client) -> Fut,
+        Fut: Future<Output = Result<PromqlResult, Error>>,
+    {
+        let requests = self.clients.iter().map(|(source, client)| {
+            let response = f(client.clone());
+            async move { (source, response.await) }
+        });
+
+        let mut results = vec![];
+        let mut errors = vec![];
+
+        for (source, result) in join_all(requests).await {
+            match result {
+                Ok(mut r) => {
+                    attach_label(&mut r.data, &self.label, source);
+                    results.push(r);
+                }
+                Err(e) => errors.push((source.clone(), e)),
+            }
+        }
+
+        FanOutResult {
+            result: merge_results(results),
+            errors,
+        }
+    }
+}
+
+/// The merged result of a [`FanOut`] query along with the errors of all servers that failed.
+/// If all servers failed the result is an empty vector.
+#[derive(Debug)]
+pub struct FanOutResult {
+    result: PromqlResult,
+    errors: Vec<(String, Error)>,
+}
+
+impl FanOutResult {
+    /// Return the merged result.
+    pub fn result(&self) -> &PromqlResult {
+        &self.result
+    }
+
+    /// Return the errors of the servers that failed along with their value of the source label.
+    pub fn errors(&self) -> &[(String, Error)] {
+        &self.errors
+    }
+
+    /// Check if all servers responded successfully.
+    pub fn is_complete(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Return the merged result and the errors.
+    pub fn into_inner(self) -> (PromqlResult, Vec<(String, Error)>) {
+        (self.result, self.errors)
+    }
+}
+
+fn attach_label(data: &mut Data, label: &str, value: &str) {
+    match data {
+        Data::Vector(vectors) => {
+            for vector in vectors {
+                vector.metric.insert(label.to_string(), value.to_string());
+            }
+        }
+        Data::Matrix(matrix) => {
+            for series in matrix {
+                series.metric.insert(label.to_string(), value.to_string());
+            }
+        }
+        _ => {}
+    }
+}
+
+// Merge the results of the same query from several servers. Series are identified by their
+// label sets. Instant vectors keep the first sample, range vectors are merged sample by sample
+// (keeping the first sample for each timestamp). Scalars and strings are taken from the first
+// result. Results whose type differs from the first one are ignored.
+pub(crate) fn merge_results(results: Vec<PromqlResult>) -> PromqlResult {
+    let mut results = results.into_iter();
+
+    let mut merged = match results.next() {
+        Some(first) => first,
+        None => {
+            return PromqlResult {
+                data: Data::Vector(vec![]),
+                stats: None,
+                annotations: Annotations::default(),
+            }
+        }
+    };
+
+    let mut index: HashMap<BTreeMap<String, String>, usize> = HashMap::new();
+
+    match &mut merged.data {
+        Data::Vector(vectors) => {
+            let first = std::mem::take(vectors);
+            merge_vectors(vectors, first, &mut index);
+        }
+        Data::Matrix(matrix) => {
+            let first = std::mem::take(matrix);
+            merge_matrix(matrix, first, &mut index);
+        }
+        _ => {}
+    }
+
+    for result in results {
+        merged.annotations.merge(result.annotations);
+
+        match (&mut merged.data, result.data) {
+            (Data::Vector(vectors), Data::Vector(others)) => {
+                merge_vectors(vectors, others, &mut index);
+            }
+            (Data::Matrix(matrix), Data::Matrix(others)) => {
+                merge_matrix(matrix, others, &mut index);
+            }
+            _ => {}
+        }
+    }
+
+    merged
+}
+
+fn merge_vectors(
+    vectors: &mut Vec<InstantVector>,
+    others: Vec<InstantVector>,
+    index: &mut HashMap<BTreeMap<String, String>, usize>,
+) {
+    for vector in others {
+        if let Entry::Vacant(e) = index.entry(label_set(&vector.metric)) {
+            e.insert(vectors.len());
+            vectors.push(vector);
+        }
+    }
+}
+
+fn merge_matrix(
+    matrix: &mut Vec<RangeVector>,
+    others: Vec<RangeVector>,
+    index: &mut HashMap<BTreeMap<String, String>, usize>,
+) {
+    for series in others {
+        let key = label_set(&series.metric);
+        match index.get(&key) {
+            Some(&i) => {
+                let existing = &mut matrix[i];
+                existing.samples = merge_samples(
+                    std::mem::take(&mut existing.samples),
+                    series.samples,
+                    |s: &Sample| s.timestamp,
+                );
+                existing.histograms = merge_samples(
+                    std::mem::take(&mut existing.histograms),
+                    series.histograms,
+                    |s: &HistogramSample| s.timestamp,
+                );
+            }
+            None => {
+                index.insert(key, matrix.len());
+                matrix.push(series);
+            }
+        }
+    }
+}
+
+// Merge two lists of samples that are sorted by timestamp, keeping the sample of the
+// first list when both contain a sample with the same timestamp.
+pub(crate) fn merge_samples<S>(a: Vec<S>, b: Vec<S>, timestamp: impl Fn(&S) -> f64) -> Vec<S> {
+    let mut merged = Vec::with_capacity(a.len().max(b.len()));
+    let mut a = a.into_iter().peekable();
+    let mut b = b.into_iter().peekable();
+
+    loop {
+        let next = match (a.peek(), b.peek()) {
+            (Some(x), Some(y)) => {
+                let (tx, ty) = (timestamp(x), timestamp(y));
+                if tx == ty {
+                    b.next();
+                    a.next()
+                } else if tx < ty {
+                    a.next()
+                } else {
+                    b.next()
+                }
+            }
+            (Some(_), None) => a.next(),
+            (None, Some(_)) => b.next(),
+            (None, None) => break,
+        };
+        merged.extend(next);
+    }
+
+    merged
+}
+
+pub(crate) fn label_set(metric: &HashMap<String, String>) -> BTreeMap<String, String> {
+    metric.iter().map(|(k, v)| (k.clone(), v.clone())).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(json: &str) -> PromqlResult {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_merge_vectors() {
+        let a = result(
+            r#"{"resultType":"vector","result":[
+                {"metric":{"job":"a"},"value":[1,"1"]},
+                {"metric":{"job":"b"},"value":[1,"2"]}
+            ]}"#,
+        );
+        let b = result(
+            r#"{"resultType":"vector","result":[
+                {"metric":{"job":"b"},"value":[1,"3"]},
+                {"metric":{"job":"c"},"value":[1,"4"]}
+            ]}"#,
+        );
+
+        let merged = merge_results(vec![a, b]);
+        let vectors = merged.data().as_vector().unwrap();
+
+        let values: Vec<(&str, f64)> = vectors
+            .iter()
+            .map(|v| (v.metric()["job"].as_str(), v.sample().unwrap().value()))
+            .collect();
+
+        assert_eq!(values, vec![("a", 1.0), ("b", 2.0), ("c", 4.0)]);
+    }
+
+    #[test]
+    fn test_merge_matrices() {
+        let a = result(
+            r#"{"resultType":"matrix","result":[
+                {"metric":{"job":"a"},"values":[[1,"1"],[3,"3"]]}
+            ]}"#,
+        );
+        let b = result(
+            r#"{"resultType":"matrix","result":[
+                {"metric":{"job":"a"},"values":[[1,"10"],[2,"2"],[4,"4"]]},
+                {"metric":{"job":"b"},"values":[[1,"1"]]}
+            ]}"#,
+        );
+
+        let merged = merge_results(vec![a, b]);
+        let matrix = merged.data().as_matrix().unwrap();
+
+        assert_eq!(matrix.len(), 2);
+
+        let samples: Vec<(f64, f64)> = matrix[0]
+            .samples()
+            .iter()
+            .map(|s| (s.timestamp(), s.value()))
+            .collect();
+
+        assert_eq!(
+            samples,
+            vec![(1.0, 1.0), (2.0, 2.0), (3.0, 3.0), (4.0, 4.0)]
+        );
+        assert_eq!(matrix[1].metric()["job"], "b");
+    }
+
+    #[test]
+    fn test_endpoint_order() -> Result<(), anyhow::Error> {
+        let urls = ["http://a:9090", "http://b:9090", "http://c:9090"];
+
+        let client = MultiClient::new(Client::default(), urls, EndpointStrategy::RoundRobin)?;
+        let hosts = |client: &MultiClient| -> Vec<String> {
+            client
+                .order()
+                .iter()
+                .map(|e| e.client.base_url().host_str().unwrap().to_string())
+                .collect()
+        };
+
+        assert_eq!(hosts(&client), vec!["a", "b", "c"]);
+        assert_eq!(hosts(&client), vec!["b", "c", "a"]);
+
+        client.endpoints[2].healthy.store(false, Ordering::Relaxed);
+        assert_eq!(hosts(&client), vec!["a", "b", "c"]);
+        assert_eq!(hosts(&client), vec!["a", "b", "c"]);
+
+        let client = MultiClient::new(Client::default(), urls, EndpointStrategy::Failover)?;
+        client.endpoints[0].healthy.store(false, Ordering::Relaxed);
+        assert_eq!(hosts(&client), vec!["b", "c", "a"]);
+        assert_eq!(hosts(&client), vec!["b", "c", "a"]);
+
+        assert!(matches!(
+            MultiClient::new(
+                Client::default(),
+                Vec::<&str>::new(),
+                EndpointStrategy::Failover
+            ),
+            Err(Error::EmptyEndpoints)
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_is_unavailable() -> Result<(), anyhow::Error> {
+        use crate::error::{ClientError, TransportError};
+        use crate::test_util::StaticTransport;
+        use crate::transport::{HttpRequest, HttpResponse};
+
+        // Responds like a proxy in front of Prometheus with the status given as the query.
+        let proxy = StaticTransport::new(|request: &HttpRequest| {
+            let status = request.url().query_pairs().next().unwrap().1.parse()?;
+            Ok(HttpResponse::new(
+                StatusCode::from_u16(status)?,
+                "<html></html>",
+            ))
+        });
+
+        let client = Client::builder().transport(proxy).build()?;
+        for (status, unavailable) in [("502", true), ("429", true), ("404", false)] {
+            let error = client.query(status).get().await.unwrap_err();
+            assert!(matches!(error, Error::Client(_)));
+            assert_eq!(is_unavailable(&error), unavailable, "{}", status);
+        }
+
+        let client = Client::try_from("http://127.0.0.1:1")?;
+        assert!(is_unavailable(&client.query("up").get().await.unwrap_err()));
+
+        let transport = |kind: std::io::ErrorKind| {
+            Error::Transport(TransportError {
+                message: "failed to send request to server",
+                source: Box::new(std::io::Error::from(kind)),
+            })
+        };
+        assert!(is_unavailable(&transport(std::io::ErrorKind::TimedOut)));
+        assert!(!is_unavailable(&transport(std::io::ErrorKind::InvalidData)));
+
+        assert!(!is_unavailable(&Error::Client(ClientError {
+            message: "failed to serialize request parameters",
+            source: None,
+        })));
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_query_all_partial_errors() -> Result<(), anyhow::Error> {
+        use crate::test_util::{json_response, StaticTransport};
+        use crate::transport::HttpRequest;
+
+        // Only the server "a" is up.
+        let hosts = StaticTransport::new(|request: &HttpRequest| {
+            if request.url().host_str() != Some("a") {
+                return Err(std::io::Error::from(std::io::ErrorKind::ConnectionRefused).into());
+            }
+            Ok(json_response(
+                r#"{"status":"success","data":{"resultType":"vector","result":[{"metric":{"job":"a"},"value":[1,"1"]}]}}"#,
+            ))
+        });
+
+        let client = Client::builder().transport(hosts).build()?;
+
+        let multi = MultiClient::new(
+            client.clone(),
+            ["http://a:9090", "http://b:9090"],
+            EndpointStrategy::QueryAll,
+        )?;
+        let result = multi.query("up").await?;
+        assert_eq!(result.data().as_vector().unwrap().len(), 1);
+        assert_eq!(
+            result.warnings(),
+            ["http://b:9090/: failed to send request to server"]
+        );
+
+        let multi = MultiClient::new(client, ["http://b:9090"], EndpointStrategy::QueryAll)?;
+        assert!(matches!(multi.query("up").await, Err(Error::Transport(_))));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_attach_source_label() {
+        let mut eu = result(
+            r#"{"resultType":"vector","result":[
+                {"metric":{"job":"a"},"value":[1,"1"]},
+                {"metric":{"job":"b","region":"unknown"},"value":[1,"2"]}
+            ]}"#,
+        );
+        let mut us = result(
+            r#"{"resultType":"vector","result":[
+                {"metric":{"job":"a"},"value":[1,"3"]}
+            ]}"#,
+        );
+
+        attach_label(&mut eu.data, "region", "eu");
+        attach_label(&mut us.data, "region", "us");
+
+        let merged = merge_results(vec![eu, us]);
+        let vectors = merged.data().as_vector().unwrap();
+
+        let values: Vec<(&str, &str, f64)> = vectors
+            .iter()
+            .map(|v| {
+                (
+                    v.metric()["job"].as_str(),
+                    v.metric()["region"].as_str(),
+                    v.sample().unwrap().value(),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            values,
+            vec![("a", "eu", 1.0), ("b", "eu", 2.0), ("a", "us", 3.0)]
+        );
+    }
+}
@@ -2,12 +2,15 @@
 use crate::util::{AlertState, RuleHealth, TargetHealth};
 use enum_as_inner::EnumAsInner;
 use serde::Deserialize;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
+use std::sync::Arc;
 use time::{Duration, OffsetDateTime, PrimitiveDateTime};
 use url::Url;
 
-mod de {
+pub(crate) mod de {
+    use crate::error::Error;
+    use crate::PrometheusDuration;
     use serde::{
         de::{Error as SerdeError, Unexpected},
         Deserialize, Deserializer,
@@ -21,7 +24,7 @@ mod de {
         "[year repr:full][month repr:numerical][day]-[hour repr:24]:[minute]:[second]"
     );
 
-    pub(super) fn deserialize_f64<'de, D>(deserializer: D) -> Result<f64, D::Error>
+    pub(crate) fn deserialize_f64<'de, D>(deserializer: D) -> Result<f64, D::Error>
     where
         D: Deserializer<'de>,
     {
@@ -62,10 +65,6 @@ mod de {
 
     // This function is used to deserialize Prometheus duration strings like "1d" or "5m" or
     // composits like "1d12h10m".
-    // Note that this function assumes that the input string is non-empty and that the total
-    // amount of milliseconds does not exceed i64::MAX. This seems to be a reasonable assumption
-    // since the Prometheus server creates durations from Go's int64 on the server side and the
-    // int64 depicts the total amount of nanoseconds.
     pub(super) fn deserialize_prometheus_duration<'de, D>(
         deserializer: D,
     ) -> Result<Duration, D::Error>
@@ -73,55 +72,23 @@ mod de {
         D: Deserializer<'de>,
     {
         let raw_str = String::deserialize(deserializer)?;
+        parse_prometheus_duration(&raw_str).map_err(SerdeError::custom)
+    }
 
-        let mut total_milliseconds: i64 = 0;
-
-        // Add each number character to a string until a unit character is encountered.
-        // This string is then cleared to process the next number + unit.
-        let mut raw_num = String::new();
-
-        // Iterate the duration string, convert each unit to nanoseconds and add
-        // it to the total.
-        let mut duration_iter = raw_str.chars().peekable();
-
-        while let Some(item) = duration_iter.next() {
-            if ('0'..='9').contains(&item) {
-                raw_num.push(item);
-                continue;
-            }
-
-            let num = raw_num.parse::<i64>().map_err(SerdeError::custom)?;
-
-            match item {
-                'y' => {
-                    total_milliseconds += num * 1000 * 60 * 60 * 24 * 365;
-                }
-                'w' => {
-                    total_milliseconds += num * 1000 * 60 * 60 * 24 * 7;
-                }
-                'd' => {
-                    total_milliseconds += num * 1000 * 60 * 60 * 24;
-                }
-                'h' => {
-                    total_milliseconds += num * 1000 * 60 * 60;
-                }
-                'm' => {
-                    if duration_iter.next_if_eq(&'s').is_some() {
-                        total_milliseconds += num * 1000 * 60 * 60;
-                    } else {
-                        total_milliseconds += num * 1000 * 60;
-                    }
-                }
-                's' => {
-                    total_milliseconds += num * 1000;
-                }
-                _ => return Err(SerdeError::custom("invalid time duration")),
-            };
-
-            raw_num.clear();
-        }
+    // Same as `deserialize_prometheus_duration` but for optional fields.
+    pub(super) fn deserialize_option_prometheus_duration<'de, D>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Option::<String>::deserialize(deserializer)?
+            .map(|raw_str| parse_prometheus_duration(&raw_str).map_err(SerdeError::custom))
+            .transpose()
+    }
 
-        Ok(Duration::milliseconds(total_milliseconds))
+    fn parse_prometheus_duration(raw_str: &str) -> Result<Duration, Error> {
+        raw_str.parse::<PrometheusDuration>().map(Duration::from)
     }
 }
 
@@ -129,11 +96,56 @@ mod de {
 #[serde(tag = "status")]
 pub(crate) enum ApiResponse<D> {
     #[serde(alias = "success")]
-    Success { data: D },
+    Success {
+        data: D,
+        #[serde(default)]
+        warnings: Vec<String>,
+        #[serde(default)]
+        infos: Vec<String>,
+    },
     #[serde(alias = "error")]
     Error(crate::error::PrometheusError),
 }
 
+/// Warnings and informational notices that Prometheus may attach to a successful
+/// API response, e.g. `PromQL info: metric might not be a counter, name does not end in _total/_sum/_count/_bucket`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Annotations {
+    pub(crate) warnings: Vec<String>,
+    pub(crate) infos: Vec<String>,
+}
+
+impl Annotations {
+    /// Get the warnings that were returned alongside the response data.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// Get the informational notices that were returned alongside the response data.
+    pub fn infos(&self) -> &[String] {
+        &self.infos
+    }
+
+    /// Check if the response contained neither warnings nor infos.
+    pub fn is_empty(&self) -> bool {
+        self.warnings.is_empty() && self.infos.is_empty()
+    }
+
+    // Add the annotations of another response, skipping duplicates.
+    pub(crate) fn merge(&mut self, other: Annotations) {
+        for warning in other.warnings {
+            if !self.warnings.contains(&warning) {
+                self.warnings.push(warning);
+            }
+        }
+        for info in other.infos {
+            if !self.infos.contains(&info) {
+                self.infos.push(info);
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct Stats {
     timings: Timings,
@@ -221,6 +233,8 @@ pub struct PromqlResult {
     #[serde(flatten)]
     pub(crate) data: Data,
     pub(crate) stats: Option<Stats>,
+    #[serde(skip)]
+    pub(crate) annotations: Annotations,
 }
 
 impl PromqlResult {
@@ -234,9 +248,24 @@ impl PromqlResult {
         self.stats.as_ref()
     }
 
+    /// Return the warnings and infos that Prometheus attached to the response.
+    pub fn annotations(&self) -> &Annotations {
+        &self.annotations
+    }
+
+    /// Return the warnings that Prometheus attached to the response.
+    pub fn warnings(&self) -> &[String] {
+        self.annotations.warnings()
+    }
+
+    /// Return the informational notices that Prometheus attached to the response.
+    pub fn infos(&self) -> &[String] {
+        self.annotations.infos()
+    }
+
     /// Returns the inner types when ownership is required
-    pub fn into_inner(self) -> (Data, Option<Stats>) {
-        (self.data, self.stats)
+    pub fn into_inner(self) -> (Data, Option<Stats>, Annotations) {
+        (self.data, self.stats, self.annotations)
     }
 }
 
@@ -250,6 +279,8 @@ pub enum Data {
     Matrix(Vec<RangeVector>),
     #[serde(alias = "scalar")]
     Scalar(Sample),
+    #[serde(alias = "string")]
+    String(StringSample),
 }
 
 impl Data {
@@ -259,16 +290,21 @@ impl Data {
             Data::Vector(v) => v.is_empty(),
             Data::Matrix(v) => v.is_empty(),
             Data::Scalar(_) => false,
+            Data::String(_) => false,
         }
     }
 }
 
 /// A single time series containing a single data point/sample.
+///
+/// Depending on the type of the time series the data point is either a float
+/// [`Sample`] or a native [`HistogramSample`].
 #[derive(Clone, Debug, PartialEq, Deserialize)]
 pub struct InstantVector {
     pub(crate) metric: HashMap<String, String>,
     #[serde(alias = "value")]
-    pub(crate) sample: Sample,
+    pub(crate) sample: Option<Sample>,
+    pub(crate) histogram: Option<HistogramSample>,
 }
 
 impl InstantVector {
@@ -278,23 +314,40 @@ impl InstantVector {
         &self.metric
     }
 
-    /// Returns a reference to the sample of this time series.
-    pub fn sample(&self) -> &Sample {
-        &self.sample
+    /// Returns a reference to the float sample of this time series or `None`
+    /// if this time series contains a native histogram sample instead.
+    pub fn sample(&self) -> Option<&Sample> {
+        self.sample.as_ref()
+    }
+
+    /// Returns a reference to the native histogram sample of this time series or `None`
+    /// if this time series contains a float sample instead.
+    pub fn histogram(&self) -> Option<&HistogramSample> {
+        self.histogram.as_ref()
     }
 
     /// Returns the inner types when ownership is required
-    pub fn into_inner(self) -> (HashMap<String, String>, Sample) {
-        (self.metric, self.sample)
+    pub fn into_inner(
+        self,
+    ) -> (
+        HashMap<String, String>,
+        Option<Sample>,
+        Option<HistogramSample>,
+    ) {
+        (self.metric, self.sample, self.histogram)
     }
 }
 
 /// A single time series containing a range of data points/samples.
+///
+/// A time series may contain float samples, native histogram samples or both.
 #[derive(Clone, Debug, PartialEq, Deserialize)]
 pub struct RangeVector {
     pub(crate) metric: HashMap<String, String>,
-    #[serde(alias = "values")]
+    #[serde(alias = "values", default)]
     pub(crate) samples: Vec<Sample>,
+    #[serde(default)]
+    pub(crate) histograms: Vec<HistogramSample>,
 }
 
 impl RangeVector {
@@ -304,14 +357,292 @@ impl RangeVector {
         &self.metric
     }
 
-    /// Returns a reference to the set of samples of this time series.
+    /// Returns a reference to the set of float samples of this time series.
     pub fn samples(&self) -> &[Sample] {
         &self.samples
     }
 
+    /// Returns a reference to the set of native histogram samples of this time series.
+    pub fn histograms(&self) -> &[HistogramSample] {
+        &self.histograms
+    }
+
     /// Returns the inner types when ownership is required
-    pub fn into_inner(self) -> (HashMap<String, String>, Vec<Sample>) {
-        (self.metric, self.samples)
+    pub fn into_inner(self) -> (HashMap<String, String>, Vec<Sample>, Vec<HistogramSample>) {
+        (self.metric, self.samples, self.histograms)
+    }
+}
+
+/// A compact, columnar representation of a range query result ([`Data::Matrix`]) that is
+/// better suited for analytics than a collection of [`RangeVector`]s.
+///
+/// All series share a single vector of timestamps, i.e. the evaluation steps of the range query when
+/// using [`RangeQueryBuilder::get_columnar`](crate::RangeQueryBuilder::get_columnar) or otherwise the sorted
+/// union of the timestamps of all samples. The float values of each series are stored in an array of the
+/// same length that contains `NaN` wherever the series has no sample.
+/// Label names and values are interned, i.e. each distinct string is only held in memory once.
+///
+/// Note that float samples with a value of `NaN` cannot be distinguished from gaps and are
+/// therefore dropped when converting back into [`RangeVector`]s. Native histogram samples are
+/// kept as they are.
+///
+/// ```rust
+/// use prometheus_http_query::{Client, response::ColumnarMatrix};
+///
+/// #[tokio::main(flavor = "current_thread")]
+/// async fn main() -> Result<(), anyhow::Error> {
+///     let client = Client::default();
+///
+///     let matrix: ColumnarMatrix = client
+///         .query_range("up", 1648373100, 1648373300, 10.0)
+///         .get_columnar()
+///         .await?;
+///
+///     for series in matrix.series() {
+///         let up = series.values().iter().filter(|v| **v == 1.0).count();
+///         println!("{:?} was up at {} of {} steps", series.label("instance"), up, matrix.timestamps().len());
+///     }
+///
+///     let range_vectors = matrix.into_range_vectors();
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ColumnarMatrix {
+    pub(crate) timestamps: Vec<f64>,
+    pub(crate) series: Vec<ColumnarSeries>,
+    pub(crate) strings: HashSet<Arc<str>>,
+    pub(crate) annotations: Annotations,
+}
+
+impl ColumnarMatrix {
+    /// Create an empty matrix.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Create an empty matrix with the given sorted timestamps, e.g. the evaluation steps of a
+    // range query, so that series only need to be merged into the shared timestamps if their
+    // samples are off this grid.
+    pub(crate) fn with_timestamps(timestamps: Vec<f64>) -> Self {
+        ColumnarMatrix {
+            timestamps,
+            ..Default::default()
+        }
+    }
+
+    /// Return the timestamps that are shared by all series.
+    pub fn timestamps(&self) -> &[f64] {
+        &self.timestamps
+    }
+
+    /// Return the series of this matrix.
+    pub fn series(&self) -> &[ColumnarSeries] {
+        &self.series
+    }
+
+    /// Return the number of series.
+    pub fn len(&self) -> usize {
+        self.series.len()
+    }
+
+    /// Check if the matrix contains no series.
+    pub fn is_empty(&self) -> bool {
+        self.series.is_empty()
+    }
+
+    /// Return the warnings and infos that Prometheus attached to the response of the range query.
+    pub fn annotations(&self) -> &Annotations {
+        &self.annotations
+    }
+
+    /// Append a series to the matrix. The shared timestamps are extended if the series
+    /// contains samples at timestamps that are not part of the matrix yet.
+    pub fn push(&mut self, series: RangeVector) {
+        let RangeVector {
+            metric,
+            samples,
+            histograms,
+        } = series;
+
+        let position =
+            |timestamps: &[f64], t: f64| timestamps.binary_search_by(|x| x.total_cmp(&t));
+
+        if samples
+            .iter()
+            .any(|s| position(&self.timestamps, s.timestamp).is_err())
+        {
+            self.extend_timestamps(samples.iter().map(|s| s.timestamp));
+        }
+
+        let mut values = vec![f64::NAN; self.timestamps.len()];
+        for sample in samples {
+            if let Ok(i) = position(&self.timestamps, sample.timestamp) {
+                values[i] = sample.value;
+            }
+        }
+
+        let mut labels: Vec<(Arc<str>, Arc<str>)> = metric
+            .into_iter()
+            .map(|(name, value)| (self.intern(name), self.intern(value)))
+            .collect();
+        labels.sort();
+
+        self.series.push(ColumnarSeries {
+            labels,
+            values,
+            histograms,
+        });
+    }
+
+    /// Convert the matrix back into a collection of [`RangeVector`]s.
+    pub fn into_range_vectors(self) -> Vec<RangeVector> {
+        let timestamps = self.timestamps;
+        self.series
+            .into_iter()
+            .map(|series| RangeVector {
+                metric: series
+                    .labels
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), value.to_string()))
+                    .collect(),
+                samples: timestamps
+                    .iter()
+                    .zip(series.values)
+                    .filter(|(_, value)| !value.is_nan())
+                    .map(|(timestamp, value)| Sample {
+                        timestamp: *timestamp,
+                        value,
+                    })
+                    .collect(),
+                histograms: series.histograms,
+            })
+            .collect()
+    }
+
+    fn intern(&mut self, s: String) -> Arc<str> {
+        match self.strings.get(s.as_str()) {
+            Some(interned) => Arc::clone(interned),
+            None => {
+                let interned: Arc<str> = Arc::from(s);
+                self.strings.insert(Arc::clone(&interned));
+                interned
+            }
+        }
+    }
+
+    // Merge the given timestamps into the shared timestamps and move the values of
+    // all existing series to their new positions.
+    fn extend_timestamps(&mut self, timestamps: impl Iterator<Item = f64>) {
+        let mut merged = self.timestamps.clone();
+        merged.extend(timestamps);
+        merged.sort_by(f64::total_cmp);
+        merged.dedup_by(|a, b| a.total_cmp(b).is_eq());
+
+        if merged.len() == self.timestamps.len() {
+            return;
+        }
+
+        for series in &mut self.series {
+            let mut values = vec![f64::NAN; merged.len()];
+            let mut i = 0;
+            for (timestamp, value) in self.timestamps.iter().zip(&series.values) {
+                while merged[i].total_cmp(timestamp).is_lt() {
+                    i += 1;
+                }
+                values[i] = *value;
+            }
+            series.values = values;
+        }
+
+        self.timestamps = merged;
+    }
+
+    // Remove the timestamps at which no series has a value.
+    pub(crate) fn retain_sampled_timestamps(&mut self) {
+        let sampled: Vec<bool> = (0..self.timestamps.len())
+            .map(|i| self.series.iter().any(|s| !s.values[i].is_nan()))
+            .collect();
+
+        let mut keep = sampled.iter();
+        self.timestamps.retain(|_| *keep.next().unwrap());
+
+        for series in &mut self.series {
+            let mut keep = sampled.iter();
+            series.values.retain(|_| *keep.next().unwrap());
+        }
+    }
+}
+
+impl Extend<RangeVector> for ColumnarMatrix {
+    fn extend<I: IntoIterator<Item = RangeVector>>(&mut self, iter: I) {
+        let series: Vec<RangeVector> = iter.into_iter().collect();
+
+        // Merge the timestamps of all series at once, so that the existing series are spread
+        // out at most once and every series is on the shared timestamps when it is pushed.
+        self.extend_timestamps(
+            series
+                .iter()
+                .flat_map(|s| s.samples.iter().map(|s| s.timestamp)),
+        );
+
+        for series in series {
+            self.push(series);
+        }
+    }
+}
+
+impl FromIterator<RangeVector> for ColumnarMatrix {
+    fn from_iter<I: IntoIterator<Item = RangeVector>>(iter: I) -> Self {
+        let mut matrix = ColumnarMatrix::new();
+        matrix.extend(iter);
+        matrix
+    }
+}
+
+impl From<Vec<RangeVector>> for ColumnarMatrix {
+    fn from(range_vectors: Vec<RangeVector>) -> Self {
+        range_vectors.into_iter().collect()
+    }
+}
+
+impl From<ColumnarMatrix> for Vec<RangeVector> {
+    fn from(matrix: ColumnarMatrix) -> Self {
+        matrix.into_range_vectors()
+    }
+}
+
+/// A single time series of a [`ColumnarMatrix`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColumnarSeries {
+    pub(crate) labels: Vec<(Arc<str>, Arc<str>)>,
+    pub(crate) values: Vec<f64>,
+    pub(crate) histograms: Vec<HistogramSample>,
+}
+
+impl ColumnarSeries {
+    /// Returns the set of labels (+ metric name) of this time series sorted by label name.
+    pub fn labels(&self) -> &[(Arc<str>, Arc<str>)] {
+        &self.labels
+    }
+
+    /// Returns the value of the given label, if any.
+    pub fn label(&self, name: &str) -> Option<&str> {
+        self.labels
+            .binary_search_by(|(n, _)| n.as_ref().cmp(name))
+            .ok()
+            .map(|i| self.labels[i].1.as_ref())
+    }
+
+    /// Returns the float values of this time series, one for each of the timestamps of
+    /// the [`ColumnarMatrix`]. Steps without a sample are `NaN`.
+    pub fn values(&self) -> &[f64] {
+        &self.values
+    }
+
+    /// Returns a reference to the set of native histogram samples of this time series.
+    pub fn histograms(&self) -> &[HistogramSample] {
+        &self.histograms
     }
 }
 
@@ -335,6 +666,294 @@ impl Sample {
     }
 }
 
+/// A single string value, e.g. the result of a query like `"foo"`.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct StringSample {
+    pub(crate) timestamp: f64,
+    pub(crate) value: String,
+}
+
+impl StringSample {
+    /// Returns the timestamp contained in this sample.
+    pub fn timestamp(&self) -> f64 {
+        self.timestamp
+    }
+
+    /// Returns the string value contained in this sample.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
+/// A single data point of a native histogram.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct HistogramSample {
+    pub(crate) timestamp: f64,
+    pub(crate) histogram: Histogram,
+}
+
+impl HistogramSample {
+    /// Returns the timestamp contained in this sample.
+    pub fn timestamp(&self) -> f64 {
+        self.timestamp
+    }
+
+    /// Returns the native histogram contained in this sample.
+    pub fn histogram(&self) -> &Histogram {
+        &self.histogram
+    }
+}
+
+/// A native histogram, i.e. the total count and sum of observations as well
+/// as the populated buckets.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct Histogram {
+    #[serde(deserialize_with = "de::deserialize_f64")]
+    pub(crate) count: f64,
+    #[serde(deserialize_with = "de::deserialize_f64")]
+    pub(crate) sum: f64,
+    #[serde(default)]
+    pub(crate) buckets: Vec<HistogramBucket>,
+}
+
+impl Histogram {
+    /// Returns the total count of observations.
+    pub fn count(&self) -> f64 {
+        self.count
+    }
+
+    /// Returns the sum of all observations.
+    pub fn sum(&self) -> f64 {
+        self.sum
+    }
+
+    /// Returns the populated buckets in ascending order of their boundaries.
+    pub fn buckets(&self) -> &[HistogramBucket] {
+        &self.buckets
+    }
+
+    /// Estimate the φ-quantile (0 ≤ φ ≤ 1) of the observations in this histogram,
+    /// similar to what the PromQL function `histogram_quantile` does for native histograms.
+    ///
+    /// The observations are assumed to be distributed linearly within each bucket.
+    /// Returns `NaN` if the histogram contains no observations, `-Inf` if φ < 0 and
+    /// `+Inf` if φ > 1.
+    pub fn quantile(&self, q: f64) -> f64 {
+        if q.is_nan() || self.count.is_nan() || self.count == 0.0 || self.buckets.is_empty() {
+            return f64::NAN;
+        }
+        if q < 0.0 {
+            return f64::NEG_INFINITY;
+        }
+        if q > 1.0 {
+            return f64::INFINITY;
+        }
+
+        let rank = q * self.count;
+        let mut cumulative = 0.0;
+        let mut found = &self.buckets[self.buckets.len() - 1];
+
+        for bucket in self.buckets.iter().filter(|b| b.count > 0.0) {
+            cumulative += bucket.count;
+            found = bucket;
+            if cumulative >= rank {
+                break;
+            }
+        }
+
+        if cumulative < rank {
+            return found.upper;
+        }
+
+        let (lower, upper) = self.effective_bounds(found);
+        let fraction = (rank - (cumulative - found.count)) / found.count;
+
+        lower + (upper - lower) * fraction
+    }
+
+    /// Estimate the fraction of observations between `lower` and `upper`,
+    /// similar to what the PromQL function `histogram_fraction` does.
+    ///
+    /// The observations are assumed to be distributed linearly within each bucket.
+    /// Returns `NaN` if the histogram contains no observations.
+    pub fn fraction(&self, lower: f64, upper: f64) -> f64 {
+        if self.count.is_nan() || self.count == 0.0 || lower.is_nan() || upper.is_nan() {
+            return f64::NAN;
+        }
+        if lower >= upper {
+            return 0.0;
+        }
+
+        let mut observations = 0.0;
+
+        for bucket in self.buckets.iter().filter(|b| b.count > 0.0) {
+            let (b_lower, b_upper) = self.effective_bounds(bucket);
+
+            if b_upper <= lower || b_lower >= upper {
+                continue;
+            }
+
+            if b_lower >= lower && b_upper <= upper {
+                observations += bucket.count;
+                continue;
+            }
+
+            let overlap = upper.min(b_upper) - lower.max(b_lower);
+            let width = b_upper - b_lower;
+
+            if width.is_finite() && width > 0.0 {
+                observations += bucket.count * overlap / width;
+            }
+        }
+
+        observations / self.count
+    }
+
+    // The zero bucket of a native histogram spans negative and positive values.
+    // If the histogram only contains observations on one side of zero, zero is
+    // considered to be the actual boundary of the zero bucket.
+    fn effective_bounds(&self, bucket: &HistogramBucket) -> (f64, f64) {
+        if bucket.lower < 0.0 && bucket.upper > 0.0 {
+            let has_negative = self.buckets.iter().any(|b| b.upper <= 0.0 && b.count > 0.0);
+            let has_positive = self.buckets.iter().any(|b| b.lower >= 0.0 && b.count > 0.0);
+
+            match (has_negative, has_positive) {
+                (false, true) => return (0.0, bucket.upper),
+                (true, false) => return (bucket.lower, 0.0),
+                _ => {}
+            }
+        }
+        (bucket.lower, bucket.upper)
+    }
+}
+
+/// A single bucket of a native histogram.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+pub struct HistogramBucket {
+    pub(crate) boundary_rule: BoundaryRule,
+    #[serde(deserialize_with = "de::deserialize_f64")]
+    pub(crate) lower: f64,
+    #[serde(deserialize_with = "de::deserialize_f64")]
+    pub(crate) upper: f64,
+    #[serde(deserialize_with = "de::deserialize_f64")]
+    pub(crate) count: f64,
+}
+
+impl HistogramBucket {
+    /// Returns the rule that determines if the boundaries of this bucket are inclusive or exclusive.
+    pub fn boundary_rule(&self) -> BoundaryRule {
+        self.boundary_rule
+    }
+
+    /// Returns the lower boundary of this bucket.
+    pub fn lower(&self) -> f64 {
+        self.lower
+    }
+
+    /// Returns the upper boundary of this bucket.
+    pub fn upper(&self) -> f64 {
+        self.upper
+    }
+
+    /// Returns the count of observations in this bucket.
+    pub fn count(&self) -> f64 {
+        self.count
+    }
+}
+
+/// Determines whether the boundaries of a [`HistogramBucket`] are inclusive or exclusive.
+#[derive(Debug, Copy, Clone, Deserialize, Eq, PartialEq)]
+#[serde(try_from = "u8")]
+pub enum BoundaryRule {
+    /// The lower boundary is exclusive, the upper boundary is inclusive.
+    OpenLeft,
+    /// The lower boundary is inclusive, the upper boundary is exclusive.
+    OpenRight,
+    /// Both boundaries are exclusive.
+    OpenBoth,
+    /// Both boundaries are inclusive.
+    ClosedBoth,
+}
+
+impl BoundaryRule {
+    pub fn is_open_left(&self) -> bool {
+        *self == Self::OpenLeft
+    }
+
+    pub fn is_open_right(&self) -> bool {
+        *self == Self::OpenRight
+    }
+
+    pub fn is_open_both(&self) -> bool {
+        *self == Self::OpenBoth
+    }
+
+    pub fn is_closed_both(&self) -> bool {
+        *self == Self::ClosedBoth
+    }
+}
+
+impl TryFrom<u8> for BoundaryRule {
+    type Error = String;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::OpenLeft),
+            1 => Ok(Self::OpenRight),
+            2 => Ok(Self::OpenBoth),
+            3 => Ok(Self::ClosedBoth),
+            _ => Err(format!("invalid histogram bucket boundary rule: {}", value)),
+        }
+    }
+}
+
+/// A single time series along with the exemplars that were recorded for it.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct ExemplarSeries {
+    #[serde(alias = "seriesLabels")]
+    pub(crate) series_labels: HashMap<String, String>,
+    pub(crate) exemplars: Vec<Exemplar>,
+}
+
+impl ExemplarSeries {
+    /// Returns a reference to the set of labels (+ metric name)
+    /// of this time series.
+    pub fn series_labels(&self) -> &HashMap<String, String> {
+        &self.series_labels
+    }
+
+    /// Returns a reference to the exemplars of this time series.
+    pub fn exemplars(&self) -> &[Exemplar] {
+        &self.exemplars
+    }
+}
+
+/// A single exemplar, i.e. an observation that references e.g. a trace.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct Exemplar {
+    pub(crate) labels: HashMap<String, String>,
+    #[serde(deserialize_with = "de::deserialize_f64")]
+    pub(crate) value: f64,
+    pub(crate) timestamp: f64,
+}
+
+impl Exemplar {
+    /// Returns a reference to the set of exemplar labels, e.g. `trace_id`.
+    pub fn labels(&self) -> &HashMap<String, String> {
+        &self.labels
+    }
+
+    /// Returns the observed value of this exemplar.
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    /// Returns the timestamp of this exemplar.
+    pub fn timestamp(&self) -> f64 {
+        self.timestamp
+    }
+}
+
 /// Collection of active and dropped targets as returned by the API.
 #[derive(Clone, Debug, Deserialize)]
 pub struct Targets {
@@ -528,14 +1147,14 @@ pub enum Rule {
 impl Rule {
     pub fn as_recording(&self) -> Option<&RecordingRule> {
         match self {
-            Self::Recording(rule) => Some(&rule),
+            Self::Recording(rule) => Some(rule),
             _ => None,
         }
     }
 
     pub fn as_alerting(&self) -> Option<&AlertingRule> {
         match self {
-            Self::Alerting(rule) => Some(&rule),
+            Self::Alerting(rule) => Some(rule),
             _ => None,
         }
     }
@@ -993,6 +1612,251 @@ impl RuntimeInformation {
     }
 }
 
+/// The configuration that the Prometheus server is currently running with.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(from = "RawConfig")]
+pub struct Config {
+    pub(crate) yaml: String,
+    pub(crate) parsed: Option<ParsedConfig>,
+}
+
+#[derive(Deserialize)]
+struct RawConfig {
+    yaml: String,
+}
+
+impl From<RawConfig> for Config {
+    fn from(raw: RawConfig) -> Self {
+        let parsed = serde_yaml::from_str(&raw.yaml).ok();
+        Config {
+            yaml: raw.yaml,
+            parsed,
+        }
+    }
+}
+
+impl Config {
+    /// Get the configuration as a YAML string, exactly as it was returned by the server.
+    pub fn yaml(&self) -> &str {
+        &self.yaml
+    }
+
+    /// Get a typed view of the most important configuration sections or `None`
+    /// if the YAML string could not be interpreted.
+    pub fn parsed(&self) -> Option<&ParsedConfig> {
+        self.parsed.as_ref()
+    }
+}
+
+/// A typed view of the most important sections of the Prometheus configuration.
+/// Sections that are not part of this view can be read from [`Config::yaml`].
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub struct ParsedConfig {
+    #[serde(default)]
+    pub(crate) global: GlobalConfig,
+    #[serde(default)]
+    pub(crate) rule_files: Vec<String>,
+    #[serde(default)]
+    pub(crate) scrape_configs: Vec<ScrapeConfig>,
+    pub(crate) alerting: Option<AlertingConfig>,
+}
+
+impl ParsedConfig {
+    /// Get the global configuration.
+    pub fn global(&self) -> &GlobalConfig {
+        &self.global
+    }
+
+    /// Get the list of rule file paths and globs.
+    pub fn rule_files(&self) -> &[String] {
+        &self.rule_files
+    }
+
+    /// Get the list of scrape configurations.
+    pub fn scrape_configs(&self) -> &[ScrapeConfig] {
+        &self.scrape_configs
+    }
+
+    /// Get the alerting configuration.
+    pub fn alerting(&self) -> Option<&AlertingConfig> {
+        self.alerting.as_ref()
+    }
+}
+
+/// The global section of the Prometheus configuration.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub struct GlobalConfig {
+    #[serde(
+        default,
+        deserialize_with = "de::deserialize_option_prometheus_duration"
+    )]
+    pub(crate) scrape_interval: Option<Duration>,
+    #[serde(
+        default,
+        deserialize_with = "de::deserialize_option_prometheus_duration"
+    )]
+    pub(crate) scrape_timeout: Option<Duration>,
+    #[serde(
+        default,
+        deserialize_with = "de::deserialize_option_prometheus_duration"
+    )]
+    pub(crate) evaluation_interval: Option<Duration>,
+    #[serde(default)]
+    pub(crate) external_labels: HashMap<String, String>,
+}
+
+impl GlobalConfig {
+    /// Get the default scrape interval.
+    pub fn scrape_interval(&self) -> Option<&Duration> {
+        self.scrape_interval.as_ref()
+    }
+
+    /// Get the default scrape timeout.
+    pub fn scrape_timeout(&self) -> Option<&Duration> {
+        self.scrape_timeout.as_ref()
+    }
+
+    /// Get the interval in which rules are evaluated.
+    pub fn evaluation_interval(&self) -> Option<&Duration> {
+        self.evaluation_interval.as_ref()
+    }
+
+    /// Get the labels that are attached to time series and alerts when communicating with
+    /// external systems.
+    pub fn external_labels(&self) -> &HashMap<String, String> {
+        &self.external_labels
+    }
+}
+
+/// A single scrape configuration.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct ScrapeConfig {
+    pub(crate) job_name: String,
+    #[serde(
+        default,
+        deserialize_with = "de::deserialize_option_prometheus_duration"
+    )]
+    pub(crate) scrape_interval: Option<Duration>,
+    #[serde(
+        default,
+        deserialize_with = "de::deserialize_option_prometheus_duration"
+    )]
+    pub(crate) scrape_timeout: Option<Duration>,
+    pub(crate) metrics_path: Option<String>,
+    pub(crate) scheme: Option<String>,
+    #[serde(default)]
+    pub(crate) static_configs: Vec<StaticConfig>,
+}
+
+impl ScrapeConfig {
+    /// Get the job name that is assigned to scraped metrics by default.
+    pub fn job_name(&self) -> &str {
+        &self.job_name
+    }
+
+    /// Get the scrape interval of this job.
+    pub fn scrape_interval(&self) -> Option<&Duration> {
+        self.scrape_interval.as_ref()
+    }
+
+    /// Get the scrape timeout of this job.
+    pub fn scrape_timeout(&self) -> Option<&Duration> {
+        self.scrape_timeout.as_ref()
+    }
+
+    /// Get the HTTP resource path from which metrics are scraped.
+    pub fn metrics_path(&self) -> Option<&str> {
+        self.metrics_path.as_deref()
+    }
+
+    /// Get the protocol scheme used for requests.
+    pub fn scheme(&self) -> Option<&str> {
+        self.scheme.as_deref()
+    }
+
+    /// Get the list of statically configured targets of this job.
+    pub fn static_configs(&self) -> &[StaticConfig] {
+        &self.static_configs
+    }
+}
+
+/// A set of statically configured targets.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct StaticConfig {
+    #[serde(default)]
+    pub(crate) targets: Vec<String>,
+    #[serde(default)]
+    pub(crate) labels: HashMap<String, String>,
+}
+
+impl StaticConfig {
+    /// Get the list of target addresses.
+    pub fn targets(&self) -> &[String] {
+        &self.targets
+    }
+
+    /// Get the labels assigned to all targets of this set.
+    pub fn labels(&self) -> &HashMap<String, String> {
+        &self.labels
+    }
+}
+
+/// The alerting section of the Prometheus configuration.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub struct AlertingConfig {
+    #[serde(default)]
+    pub(crate) alertmanagers: Vec<AlertmanagerConfig>,
+}
+
+impl AlertingConfig {
+    /// Get the list of alertmanager configurations.
+    pub fn alertmanagers(&self) -> &[AlertmanagerConfig] {
+        &self.alertmanagers
+    }
+}
+
+/// The configuration of a set of alertmanagers that alerts are sent to.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct AlertmanagerConfig {
+    pub(crate) scheme: Option<String>,
+    pub(crate) path_prefix: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "de::deserialize_option_prometheus_duration"
+    )]
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) api_version: Option<String>,
+    #[serde(default)]
+    pub(crate) static_configs: Vec<StaticConfig>,
+}
+
+impl AlertmanagerConfig {
+    /// Get the protocol scheme used for requests to the alertmanagers.
+    pub fn scheme(&self) -> Option<&str> {
+        self.scheme.as_deref()
+    }
+
+    /// Get the path prefix of the alertmanager API.
+    pub fn path_prefix(&self) -> Option<&str> {
+        self.path_prefix.as_deref()
+    }
+
+    /// Get the timeout used when sending alerts.
+    pub fn timeout(&self) -> Option<&Duration> {
+        self.timeout.as_ref()
+    }
+
+    /// Get the alertmanager API version.
+    pub fn api_version(&self) -> Option<&str> {
+        self.api_version.as_deref()
+    }
+
+    /// Get the list of statically configured alertmanagers.
+    pub fn static_configs(&self) -> &[StaticConfig] {
+        &self.static_configs
+    }
+}
+
 /// Prometheus TSDB statistics.
 #[derive(Clone, Debug, Deserialize)]
 pub struct TsdbStatistics {
@@ -1089,6 +1953,19 @@ impl TsdbItemCount {
     }
 }
 
+/// A TSDB snapshot as created by [`Client::snapshot`](crate::Client::snapshot).
+#[derive(Clone, Debug, Deserialize)]
+pub struct Snapshot {
+    pub(crate) name: String,
+}
+
+impl Snapshot {
+    /// Get the name of the snapshot directory below `<data-dir>/snapshots`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
 /// WAL replay state.
 #[derive(Clone, Copy, Debug, Deserialize)]
 pub struct WalReplayStatistics {
@@ -1183,7 +2060,53 @@ mod tests {
 "#;
 
         let result = serde_json::from_str::<ApiResponse<PromqlResult>>(data)?;
-        assert!(matches!(result, ApiResponse::Success { data: _ }));
+        assert!(matches!(result, ApiResponse::Success { .. }));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_api_success_with_annotations_deserialization() -> Result<(), anyhow::Error> {
+        let data = r#"
+{
+  "status": "success",
+  "data": {
+    "resultType": "vector",
+    "result": []
+  },
+  "warnings": [
+    "PromQL warning: encountered a mix of histograms and floats for metric name \"foo\""
+  ],
+  "infos": [
+    "PromQL info: metric might not be a counter, name does not end in _total/_sum/_count/_bucket: \"foo\" (1:6)"
+  ]
+}
+"#;
+
+        let result = serde_json::from_str::<ApiResponse<PromqlResult>>(data)?;
+        match result {
+            ApiResponse::Success {
+                warnings, infos, ..
+            } => {
+                assert!(warnings.len() == 1);
+                assert!(warnings[0].starts_with("PromQL warning"));
+                assert!(infos.len() == 1);
+                assert!(infos[0].starts_with("PromQL info"));
+            }
+            ApiResponse::Error(_) => panic!("expected a successful response"),
+        }
+
+        let data = r#"
+{
+  "status": "success",
+  "data": []
+}
+"#;
+
+        let result = serde_json::from_str::<ApiResponse<Vec<String>>>(data)?;
+        assert!(
+            matches!(result, ApiResponse::Success { warnings, infos, .. } if warnings.is_empty() && infos.is_empty())
+        );
 
         Ok(())
     }
@@ -1435,6 +2358,27 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_query_result_string_deserialization() -> Result<(), anyhow::Error> {
+        let data = r#"
+{
+  "resultType": "string",
+  "result": [
+    1435781451.781,
+    "foo"
+  ]
+}
+"#;
+        let result = serde_json::from_str::<PromqlResult>(data)?;
+        let data = result.data();
+        assert!(data.is_string());
+        assert!(!data.is_empty());
+        let string = data.as_string().unwrap();
+        assert!(string.timestamp() == 1435781451.781);
+        assert!(string.value() == "foo");
+        Ok(())
+    }
+
     #[test]
     fn test_instant_vector_deserialization() -> Result<(), anyhow::Error> {
         let data = r#"
@@ -1519,6 +2463,264 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_columnar_matrix() -> Result<(), anyhow::Error> {
+        let data = r#"
+[
+  {
+    "metric": { "__name__": "up", "job": "node", "instance": "a" },
+    "values": [ [ 10, "1" ], [ 20, "1" ], [ 30, "0" ] ]
+  },
+  {
+    "metric": { "__name__": "up", "job": "node", "instance": "b" },
+    "values": [ [ 20, "1" ], [ 40, "1" ] ]
+  },
+  {
+    "metric": { "__name__": "up", "job": "node", "instance": "c" },
+    "values": [ [ 15, "2" ] ]
+  }
+]
+"#;
+        let range_vectors = serde_json::from_str::<Vec<RangeVector>>(data)?;
+        let matrix = ColumnarMatrix::from(range_vectors.clone());
+
+        assert_eq!(matrix.len(), 3);
+        assert_eq!(matrix.timestamps(), [10.0, 15.0, 20.0, 30.0, 40.0]);
+
+        let values: Vec<Vec<String>> = matrix
+            .series()
+            .iter()
+            .map(|s| s.values().iter().map(|v| v.to_string()).collect())
+            .collect();
+        assert_eq!(
+            values,
+            [
+                ["1", "NaN", "1", "0", "NaN"],
+                ["NaN", "NaN", "1", "NaN", "1"],
+                ["NaN", "2", "NaN", "NaN", "NaN"],
+            ]
+        );
+
+        let (a, b) = (&matrix.series()[0], &matrix.series()[1]);
+        assert_eq!(a.label("instance"), Some("a"));
+        assert_eq!(a.label("missing"), None);
+        assert_eq!(a.labels()[0].0.as_ref(), "__name__");
+        assert!(Arc::ptr_eq(&a.labels()[2].1, &b.labels()[2].1));
+
+        assert_eq!(matrix.into_range_vectors(), range_vectors);
+        Ok(())
+    }
+
+    #[test]
+    fn test_native_histogram_instant_vector_deserialization() -> Result<(), anyhow::Error> {
+        let data = r#"
+[
+  {
+    "metric": {
+      "__name__": "prometheus_http_request_duration_seconds",
+      "handler": "/api/v1/query"
+    },
+    "histogram": [
+      1700000000,
+      {
+        "count": "10",
+        "sum": "3.5",
+        "buckets": [
+          [0, "0.25", "0.5", "4"],
+          [0, "0.5", "1", "6"]
+        ]
+      }
+    ]
+  },
+  {
+    "metric": {
+      "__name__": "up"
+    },
+    "value": [
+      1700000000,
+      "1"
+    ]
+  }
+]
+"#;
+        let vectors = serde_json::from_str::<Vec<InstantVector>>(data)?;
+        assert!(vectors.len() == 2);
+        assert!(vectors[0].sample().is_none());
+        let sample = vectors[0].histogram().unwrap();
+        assert!(sample.timestamp() == 1700000000.0);
+        let histogram = sample.histogram();
+        assert!(histogram.count() == 10.0);
+        assert!(histogram.sum() == 3.5);
+        assert!(histogram.buckets().len() == 2);
+        let bucket = &histogram.buckets()[1];
+        assert!(bucket.boundary_rule().is_open_left());
+        assert!(bucket.lower() == 0.5);
+        assert!(bucket.upper() == 1.0);
+        assert!(bucket.count() == 6.0);
+        assert!(vectors[1].histogram().is_none());
+        assert!(vectors[1].sample().is_some_and(|s| s.value() == 1.0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_native_histogram_range_vector_deserialization() -> Result<(), anyhow::Error> {
+        let data = r#"
+[
+  {
+    "metric": {
+      "__name__": "prometheus_http_request_duration_seconds"
+    },
+    "values": [
+      [1700000000, "1"]
+    ],
+    "histograms": [
+      [1700000015, { "count": "1", "sum": "0.1", "buckets": [[3, "0", "0", "1"]] }],
+      [1700000030, { "count": "0", "sum": "0" }]
+    ]
+  }
+]
+"#;
+        let vectors = serde_json::from_str::<Vec<RangeVector>>(data)?;
+        assert!(vectors[0].samples().len() == 1);
+        let histograms = vectors[0].histograms();
+        assert!(histograms.len() == 2);
+        assert!(histograms[0].histogram().buckets()[0]
+            .boundary_rule()
+            .is_closed_both());
+        assert!(histograms[1].histogram().buckets().is_empty());
+
+        let data =
+            r#"[[1700000015, { "count": "1", "sum": "0.1", "buckets": [[4, "0", "1", "1"]] }]]"#;
+        assert!(serde_json::from_str::<Vec<HistogramSample>>(data).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_native_histogram_quantile() {
+        let histogram = Histogram {
+            count: 10.0,
+            sum: 6.0,
+            buckets: vec![
+                HistogramBucket {
+                    boundary_rule: BoundaryRule::OpenLeft,
+                    lower: 0.25,
+                    upper: 0.5,
+                    count: 4.0,
+                },
+                HistogramBucket {
+                    boundary_rule: BoundaryRule::OpenLeft,
+                    lower: 0.5,
+                    upper: 1.0,
+                    count: 6.0,
+                },
+            ],
+        };
+        assert!(histogram.quantile(0.0) == 0.25);
+        assert!(histogram.quantile(0.2) == 0.375);
+        assert!(histogram.quantile(0.4) == 0.5);
+        assert!(histogram.quantile(0.7) == 0.75);
+        assert!(histogram.quantile(1.0) == 1.0);
+        assert!(histogram.quantile(-1.0) == f64::NEG_INFINITY);
+        assert!(histogram.quantile(2.0) == f64::INFINITY);
+        assert!(histogram.fraction(0.0, 0.5) == 0.4);
+        assert!(histogram.fraction(0.375, 0.75) == 0.5);
+        assert!(histogram.fraction(f64::NEG_INFINITY, f64::INFINITY) == 1.0);
+        assert!(histogram.fraction(1.0, 0.0) == 0.0);
+
+        let empty = Histogram {
+            count: 0.0,
+            sum: 0.0,
+            buckets: vec![],
+        };
+        assert!(empty.quantile(0.5).is_nan());
+        assert!(empty.fraction(0.0, 1.0).is_nan());
+
+        let zero_bucket = Histogram {
+            count: 4.0,
+            sum: 10.0,
+            buckets: vec![
+                HistogramBucket {
+                    boundary_rule: BoundaryRule::ClosedBoth,
+                    lower: -0.5,
+                    upper: 0.5,
+                    count: 2.0,
+                },
+                HistogramBucket {
+                    boundary_rule: BoundaryRule::OpenLeft,
+                    lower: 4.0,
+                    upper: 8.0,
+                    count: 2.0,
+                },
+            ],
+        };
+        assert!(zero_bucket.quantile(0.25) == 0.25);
+        assert!(zero_bucket.fraction(0.0, 0.5) == 0.5);
+    }
+
+    #[test]
+    fn test_exemplars_deserialization() -> Result<(), anyhow::Error> {
+        let data = r#"
+[
+  {
+    "seriesLabels": {
+      "__name__": "test_exemplar_metric_total",
+      "instance": "localhost:8090",
+      "job": "prometheus",
+      "service": "bar"
+    },
+    "exemplars": [
+      {
+        "labels": {
+          "trace_id": "EpTxMJ40fUus7aGY"
+        },
+        "value": "6",
+        "timestamp": 1600096945.479
+      }
+    ]
+  },
+  {
+    "seriesLabels": {
+      "__name__": "test_exemplar_metric_total",
+      "instance": "localhost:8090",
+      "job": "prometheus",
+      "service": "foo"
+    },
+    "exemplars": [
+      {
+        "labels": {
+          "trace_id": "Olp9XHlq763ccsfa"
+        },
+        "value": "19",
+        "timestamp": 1600096955.479
+      },
+      {
+        "labels": {
+          "trace_id": "hCtjygkIHwAN9vs4"
+        },
+        "value": "20",
+        "timestamp": 1600096965.489
+      }
+    ]
+  }
+]
+"#;
+        let series = serde_json::from_str::<Vec<ExemplarSeries>>(data)?;
+        assert!(series.len() == 2);
+        assert!(series[0]
+            .series_labels()
+            .get("service")
+            .is_some_and(|v| v == "bar"));
+        assert!(series[1].exemplars().len() == 2);
+        let exemplar = &series[1].exemplars()[1];
+        assert!(exemplar
+            .labels()
+            .get("trace_id")
+            .is_some_and(|v| v == "hCtjygkIHwAN9vs4"));
+        assert!(exemplar.value() == 20.0);
+        assert!(exemplar.timestamp() == 1600096965.489);
+        Ok(())
+    }
+
     #[test]
     fn test_target_deserialization() -> Result<(), anyhow::Error> {
         let data = r#"
@@ -1990,7 +3192,54 @@ mod tests {
   "storageRetention": "15d"
 }
 "#;
-        serde_json::from_str::<RuntimeInformation>(data)?;
+        let info = serde_json::from_str::<RuntimeInformation>(data)?;
+        assert_eq!(info.storage_retention(), &Duration::days(15));
+
+        let data = data.replace("\"15d\"", "\"1d12h500ms\"");
+        let info = serde_json::from_str::<RuntimeInformation>(&data)?;
+        assert_eq!(
+            info.storage_retention(),
+            &(Duration::hours(36) + Duration::milliseconds(500))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_deserialization() -> Result<(), anyhow::Error> {
+        let data = r#"
+{
+  "yaml": "global:\n  scrape_interval: 15s\n  scrape_timeout: 10s\n  evaluation_interval: 1m\n  external_labels:\n    region: eu-west-1\nalerting:\n  alertmanagers:\n  - follow_redirects: true\n    scheme: http\n    timeout: 10s\n    api_version: v2\n    static_configs:\n    - targets:\n      - alertmanager:9093\nrule_files:\n- /etc/prometheus/rules/*.yaml\nscrape_configs:\n- job_name: prometheus\n  honor_timestamps: true\n  scrape_interval: 15s\n  scrape_timeout: 10s\n  metrics_path: /metrics\n  scheme: http\n  static_configs:\n  - targets:\n    - localhost:9090\n- job_name: node\n  scrape_interval: 1m\n  scrape_timeout: 10s\n  metrics_path: /metrics\n  scheme: http\n"
+}
+"#;
+        let config = serde_json::from_str::<Config>(data)?;
+        assert!(config.yaml().starts_with("global:"));
+        let parsed = config.parsed().unwrap();
+        let global = parsed.global();
+        assert!(global.scrape_interval() == Some(&Duration::seconds(15)));
+        assert!(global.scrape_timeout() == Some(&Duration::seconds(10)));
+        assert!(global.evaluation_interval() == Some(&Duration::minutes(1)));
+        assert!(global
+            .external_labels()
+            .get("region")
+            .is_some_and(|v| v == "eu-west-1"));
+        assert!(parsed.rule_files() == ["/etc/prometheus/rules/*.yaml"]);
+        let scrape_configs = parsed.scrape_configs();
+        assert!(scrape_configs.len() == 2);
+        assert!(scrape_configs[0].job_name() == "prometheus");
+        assert!(scrape_configs[0].static_configs()[0].targets() == ["localhost:9090"]);
+        assert!(scrape_configs[1].job_name() == "node");
+        assert!(scrape_configs[1].scrape_interval() == Some(&Duration::minutes(1)));
+        assert!(scrape_configs[1].metrics_path() == Some("/metrics"));
+        let alertmanagers = parsed.alerting().unwrap().alertmanagers();
+        assert!(alertmanagers.len() == 1);
+        assert!(alertmanagers[0].api_version() == Some("v2"));
+        assert!(alertmanagers[0].timeout() == Some(&Duration::seconds(10)));
+        assert!(alertmanagers[0].static_configs()[0].targets() == ["alertmanager:9093"]);
+
+        let data = r#"{ "yaml": "global: [" }"#;
+        let config = serde_json::from_str::<Config>(data)?;
+        assert!(config.yaml() == "global: [");
+        assert!(config.parsed().is_none());
         Ok(())
     }
 
@@ -2050,6 +3299,18 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_snapshot_deserialization() -> Result<(), anyhow::Error> {
+        let data = r#"
+{
+  "name": "20171210T211224Z-2be650b6d019eb54"
+}
+"#;
+        let snapshot = serde_json::from_str::<Snapshot>(data)?;
+        assert!(snapshot.name() == "20171210T211224Z-2be650b6d019eb54");
+        Ok(())
+    }
+
     #[test]
     fn test_wal_replay_deserialization() -> Result<(), anyhow::Error> {
         let data = r#"
@@ -0,0 +1,299 @@
+use reqwest::header::RETRY_AFTER;
+use reqwest::StatusCode;
+use std::collections::hash_map::RandomState;
+use std::error::Error as StdError;
+use std::hash::{BuildHasher, Hasher};
+use std::io;
+use std::time::Duration;
+use time::{format_description::well_known::Rfc2822, OffsetDateTime};
+
+/// A policy to automatically retry requests that failed due to transient errors,
+/// see [`ClientBuilder::retry`](crate::ClientBuilder::retry).
+///
+/// Requests are retried using exponential backoff, i.e. the delay between two attempts is
+/// doubled after each attempt starting with the initial backoff and capped by the maximum backoff.
+/// By default the delay is randomized ("full jitter") in order to avoid many clients retrying in lockstep.
+///
+/// The default policy makes at most 3 attempts and retries on connection errors, timeouts and the
+/// following HTTP status codes: 429, 502, 503 and 504. Note that Prometheus responds with 503 when
+/// a query times out or the server is not ready yet (error types [`timeout`](crate::error::PrometheusErrorType::Timeout)
+/// and [`unavailable`](crate::error::PrometheusErrorType::Unavailable)).
+///
+/// Only requests that are safe to repeat are retried, i.e. requests to the admin API that modify
+/// data on the server (e.g. [`Client::snapshot`](crate::Client::snapshot)) are sent exactly once.
+///
+/// ```rust
+/// use prometheus_http_query::{Client, RetryPolicy};
+/// use std::time::Duration;
+///
+/// fn main() -> Result<(), anyhow::Error> {
+///     let policy = RetryPolicy::new()
+///         .max_attempts(5)
+///         .initial_backoff(Duration::from_millis(200))
+///         .max_backoff(Duration::from_secs(5))
+///         .status_codes([500, 502, 503, 504]);
+///
+///     let client = Client::builder().retry(policy).build()?;
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    jitter: bool,
+    status_codes: Vec<StatusCode>,
+    retry_connect: bool,
+    retry_timeout: bool,
+    respect_retry_after: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10),
+            jitter: true,
+            status_codes: vec![
+                StatusCode::TOO_MANY_REQUESTS,
+                StatusCode::BAD_GATEWAY,
+                StatusCode::SERVICE_UNAVAILABLE,
+                StatusCode::GATEWAY_TIMEOUT,
+            ],
+            retry_connect: true,
+            retry_timeout: true,
+            respect_retry_after: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Create the default retry policy.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum number of attempts including the initial request. A value of 1 disables retries.
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Set the delay before the first retry.
+    pub fn initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    /// Set the maximum delay between two attempts. This also caps delays requested
+    /// by the server using the `Retry-After` header.
+    pub fn max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    /// Enable or disable randomization of the delay between two attempts.
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Set the HTTP status codes that are considered to be retryable. Invalid status codes are ignored.
+    /// Calling this repeatedly will replace the current set of status codes.
+    pub fn status_codes<T>(mut self, status_codes: T) -> Self
+    where
+        T: IntoIterator<Item = u16>,
+    {
+        self.status_codes = status_codes
+            .into_iter()
+            .filter_map(|c| StatusCode::from_u16(c).ok())
+            .collect();
+        self
+    }
+
+    /// Enable or disable retries when a connection to the server cannot be established
+    /// or is closed or reset before a response arrived.
+    pub fn retry_connect_errors(mut self, retry: bool) -> Self {
+        self.retry_connect = retry;
+        self
+    }
+
+    /// Enable or disable retries when a request times out.
+    pub fn retry_timeouts(mut self, retry: bool) -> Self {
+        self.retry_timeout = retry;
+        self
+    }
+
+    /// Enable or disable waiting for the delay that the server requested using the `Retry-After` header
+    /// instead of the computed backoff.
+    pub fn respect_retry_after(mut self, respect: bool) -> Self {
+        self.respect_retry_after = respect;
+        self
+    }
+
+    // Return the delay to wait before the next attempt if the response should be retried.
+    pub(crate) fn retry_response(
+        &self,
+        attempt: u32,
+        response: &reqwest::Response,
+    ) -> Option<Duration> {
+        if attempt >= self.max_attempts || !self.status_codes.contains(&response.status()) {
+            return None;
+        }
+
+        let retry_after = response
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| parse_retry_after(v, OffsetDateTime::now_utc()));
+
+        match retry_after {
+            Some(delay) if self.respect_retry_after => Some(delay.min(self.max_backoff)),
+            _ => Some(self.backoff(attempt)),
+        }
+    }
+
+    // Return the delay to wait before the next attempt if the error should be retried.
+    pub(crate) fn retry_error(
+        &self,
+        attempt: u32,
+        error: &(dyn StdError + 'static),
+    ) -> Option<Duration> {
+        let retryable =
+            (self.retry_connect && is_connect(error)) || (self.retry_timeout && is_timeout(error));
+
+        if attempt >= self.max_attempts || !retryable {
+            return None;
+        }
+
+        Some(self.backoff(attempt))
+    }
+
+    // Exponential backoff after the given (1-based) attempt.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff);
+
+        if self.jitter {
+            backoff.mul_f64(random_fraction())
+        } else {
+            backoff
+        }
+    }
+}
+
+// Connection errors and timeouts are recognized anywhere in the chain of errors, either as a
+// `reqwest::Error` or as a `std::io::Error`, so that custom transports can report them as well.
+pub(crate) fn is_connect(error: &(dyn StdError + 'static)) -> bool {
+    chain(error).any(|e| match e.downcast_ref::<reqwest::Error>() {
+        // Besides errors while connecting, this covers connections that were closed or reset
+        // before a response arrived, e.g. keep-alive connections that the server closed.
+        Some(e) => e.is_connect() || (e.is_request() && !e.is_timeout() && e.status().is_none()),
+        None => matches!(
+            e.downcast_ref::<io::Error>().map(io::Error::kind),
+            Some(
+                io::ErrorKind::ConnectionRefused
+                    | io::ErrorKind::ConnectionReset
+                    | io::ErrorKind::ConnectionAborted
+                    | io::ErrorKind::NotConnected
+            )
+        ),
+    })
+}
+
+pub(crate) fn is_timeout(error: &(dyn StdError + 'static)) -> bool {
+    chain(error).any(|e| match e.downcast_ref::<reqwest::Error>() {
+        Some(e) => e.is_timeout(),
+        None => matches!(
+            e.downcast_ref::<io::Error>().map(io::Error::kind),
+            Some(io::ErrorKind::TimedOut)
+        ),
+    })
+}
+
+fn chain<'a>(
+    error: &'a (dyn StdError + 'static),
+) -> impl Iterator<Item = &'a (dyn StdError + 'static)> {
+    std::iter::successors(Some(error), |e| (*e).source())
+}
+
+// The `Retry-After` header contains either a number of seconds or an HTTP date.
+fn parse_retry_after(value: &str, now: OffsetDateTime) -> Option<Duration> {
+    let value = value.trim();
+
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = OffsetDateTime::parse(value, &Rfc2822).ok()?;
+    let delay = date - now;
+    Some(delay.try_into().unwrap_or(Duration::ZERO))
+}
+
+// A random number in [0, 1). This is not suitable for anything but jitter.
+fn random_fraction() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default(),
+    );
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy::new()
+            .jitter(false)
+            .initial_backoff(Duration::from_millis(100))
+            .max_backoff(Duration::from_millis(350));
+
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(350));
+        assert_eq!(policy.backoff(100), Duration::from_millis(350));
+
+        let policy = policy.jitter(true);
+        for attempt in 1..10 {
+            assert!(policy.backoff(attempt) <= Duration::from_millis(350));
+        }
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        let now = datetime!(2015-10-21 07:28:00 UTC);
+
+        assert_eq!(
+            parse_retry_after("120", now),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:30 GMT", now),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:27:00 GMT", now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
+    #[test]
+    fn test_status_codes() {
+        let policy = RetryPolicy::new().status_codes([500, 1000]);
+        assert_eq!(policy.status_codes, vec![StatusCode::INTERNAL_SERVER_ERROR]);
+        assert_eq!(RetryPolicy::new().max_attempts(0).max_attempts, 1);
+    }
+}
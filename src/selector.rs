@@ -1,20 +1,46 @@
+use crate::error::{Error, InvalidSelectorError, ParseSelectorError};
 use crate::util::*;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt;
+use std::str::FromStr;
 
 /// A time series selector that is gradually built from a metric name and/or
 /// a set of label matchers.
-#[derive(Debug, Clone, PartialEq)]
-pub struct Selector<'a> {
-    pub(crate) labels: Vec<Label<'a>>,
+///
+/// Label values are escaped according to the PromQL string rules when the selector
+/// is rendered. Label names that are not valid according to the legacy naming rules
+/// (`[a-zA-Z_][a-zA-Z0-9_]*`) are quoted as supported by Prometheus 3.x,
+/// e.g. `{"service.name"="api"}`. Use the `try_*` methods (e.g. [`Selector::try_eq`]) in order
+/// to reject such names instead.
+///
+/// A [`Selector`] can also be parsed from its PromQL representation and is (de)serialized
+/// as such using [`serde`].
+///
+/// ```rust
+/// use prometheus_http_query::Selector;
+///
+/// let select: Selector = r#"http_requests_total{job="api",env!="dev"}"#.parse()?;
+///
+/// let expected = Selector::new()
+///     .metric("http_requests_total")
+///     .eq("job", "api")
+///     .ne("env", "dev");
+///
+/// assert_eq!(select, expected);
+/// # Ok::<(), prometheus_http_query::Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Selector {
+    pub(crate) labels: Vec<Label>,
 }
 
-impl<'a> Default for Selector<'a> {
+impl Default for Selector {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl<'a> Selector<'a> {
+impl Selector {
     /// Create a new instance of [Selector].
     pub fn new() -> Self {
         Selector { labels: vec![] }
@@ -32,11 +58,12 @@ impl<'a> Selector<'a> {
     ///
     /// assert_eq!(select, other_select);
     /// ```
-    pub fn metric(mut self, metric: &'a str) -> Self
+    pub fn metric(mut self, metric: impl Into<String>) -> Self
     where
         Self: Sized,
     {
-        self.labels.push(Label::Equal(("__name__", metric)));
+        self.labels
+            .push(Label::Equal((String::from("__name__"), metric.into())));
         self
     }
 
@@ -56,11 +83,11 @@ impl<'a> Selector<'a> {
     ///
     /// assert_eq!(select, expected);
     /// ```
-    pub fn eq(mut self, label: &'a str, value: &'a str) -> Self
+    pub fn eq(mut self, label: impl Into<String>, value: impl Into<String>) -> Self
     where
         Self: Sized,
     {
-        self.labels.push(Label::Equal((label, value)));
+        self.labels.push(Label::Equal((label.into(), value.into())));
         self
     }
 
@@ -80,11 +107,12 @@ impl<'a> Selector<'a> {
     ///
     /// assert_eq!(select, expected);
     /// ```
-    pub fn ne(mut self, label: &'a str, value: &'a str) -> Self
+    pub fn ne(mut self, label: impl Into<String>, value: impl Into<String>) -> Self
     where
         Self: Sized,
     {
-        self.labels.push(Label::NotEqual((label, value)));
+        self.labels
+            .push(Label::NotEqual((label.into(), value.into())));
         self
     }
 
@@ -104,11 +132,12 @@ impl<'a> Selector<'a> {
     ///
     /// assert_eq!(select, expected);
     /// ```
-    pub fn regex_eq(mut self, label: &'a str, value: &'a str) -> Self
+    pub fn regex_eq(mut self, label: impl Into<String>, value: impl Into<String>) -> Self
     where
         Self: Sized,
     {
-        self.labels.push(Label::RegexEqual((label, value)));
+        self.labels
+            .push(Label::RegexEqual((label.into(), value.into())));
         self
     }
 
@@ -128,16 +157,95 @@ impl<'a> Selector<'a> {
     ///
     /// assert_eq!(select, expected);
     /// ```
-    pub fn regex_ne(mut self, label: &'a str, value: &'a str) -> Self
+    pub fn regex_ne(mut self, label: impl Into<String>, value: impl Into<String>) -> Self
     where
         Self: Sized,
     {
-        self.labels.push(Label::RegexNotEqual((label, value)));
+        self.labels
+            .push(Label::RegexNotEqual((label.into(), value.into())));
         self
     }
+
+    /// Fallible version of [`Selector::metric`] that returns [`Error::InvalidSelector`]
+    /// if the metric name is not valid according to the legacy naming rules (`[a-zA-Z_:][a-zA-Z0-9_:]*`).
+    ///
+    /// ```rust
+    /// use prometheus_http_query::Selector;
+    ///
+    /// assert!(Selector::new().try_metric("http_requests_total").is_ok());
+    /// assert!(Selector::new().try_metric("http.requests.total").is_err());
+    /// ```
+    pub fn try_metric(self, metric: impl Into<String>) -> Result<Self, Error> {
+        let metric = metric.into();
+        if !is_legacy_metric_name(&metric) {
+            return Err(Error::InvalidSelector(InvalidSelectorError {
+                message: "invalid metric name",
+                name: metric.clone(),
+            }));
+        }
+        Ok(self.metric(metric))
+    }
+
+    /// Fallible version of [`Selector::eq`] that returns [`Error::InvalidSelector`]
+    /// if the label name is not valid according to the legacy naming rules (`[a-zA-Z_][a-zA-Z0-9_]*`).
+    ///
+    /// ```rust
+    /// use prometheus_http_query::Selector;
+    ///
+    /// assert!(Selector::new().try_eq("job", "api").is_ok());
+    /// assert!(Selector::new().try_eq("service.name", "api").is_err());
+    /// ```
+    pub fn try_eq(self, label: impl Into<String>, value: impl Into<String>) -> Result<Self, Error> {
+        let label = label.into();
+        validate_label_name(&label)?;
+        Ok(self.eq(label, value))
+    }
+
+    /// Fallible version of [`Selector::ne`] that returns [`Error::InvalidSelector`]
+    /// if the label name is not valid according to the legacy naming rules (`[a-zA-Z_][a-zA-Z0-9_]*`).
+    pub fn try_ne(self, label: impl Into<String>, value: impl Into<String>) -> Result<Self, Error> {
+        let label = label.into();
+        validate_label_name(&label)?;
+        Ok(self.ne(label, value))
+    }
+
+    /// Fallible version of [`Selector::regex_eq`] that returns [`Error::InvalidSelector`]
+    /// if the label name is not valid according to the legacy naming rules (`[a-zA-Z_][a-zA-Z0-9_]*`).
+    pub fn try_regex_eq(
+        self,
+        label: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Result<Self, Error> {
+        let label = label.into();
+        validate_label_name(&label)?;
+        Ok(self.regex_eq(label, value))
+    }
+
+    /// Fallible version of [`Selector::regex_ne`] that returns [`Error::InvalidSelector`]
+    /// if the label name is not valid according to the legacy naming rules (`[a-zA-Z_][a-zA-Z0-9_]*`).
+    pub fn try_regex_ne(
+        self,
+        label: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Result<Self, Error> {
+        let label = label.into();
+        validate_label_name(&label)?;
+        Ok(self.regex_ne(label, value))
+    }
 }
 
-impl<'a> fmt::Display for Selector<'a> {
+fn validate_label_name(label: &str) -> Result<(), Error> {
+    if is_legacy_label_name(label) {
+        Ok(())
+    } else {
+        Err(Error::InvalidSelector(InvalidSelectorError {
+            message: "invalid label name",
+            name: label.to_string(),
+        }))
+    }
+}
+
+impl fmt::Display for Selector {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         let matchers = self
             .labels
@@ -149,6 +257,257 @@ impl<'a> fmt::Display for Selector<'a> {
     }
 }
 
+impl FromStr for Selector {
+    type Err = Error;
+
+    /// Parse a selector like `http_requests_total{job="api",env!="dev"}`, `{job="api"}`
+    /// or `http_requests_total`. Quoted (UTF-8) metric and label names as introduced in
+    /// Prometheus 3.x are supported as well, e.g. `{"service.name"="api"}`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Parser { input: s, pos: 0 }.parse()
+    }
+}
+
+impl Serialize for Selector {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Selector {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn parse(mut self) -> Result<Selector, Error> {
+        let mut selector = Selector::new();
+
+        self.skip_whitespace();
+
+        if self
+            .peek()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == ':')
+        {
+            let metric = self.identifier();
+            selector = selector.metric(metric);
+            self.skip_whitespace();
+        }
+
+        if self.peek() == Some('{') {
+            self.bump();
+            self.matchers(&mut selector)?;
+        } else if selector.labels.is_empty() {
+            return Err(self.error("expected metric name or '{'"));
+        }
+
+        self.skip_whitespace();
+
+        if self.pos < self.input.len() {
+            return Err(self.error("unexpected character"));
+        }
+
+        Ok(selector)
+    }
+
+    fn matchers(&mut self, selector: &mut Selector) -> Result<(), Error> {
+        loop {
+            self.skip_whitespace();
+
+            let (name, quoted) = match self.peek() {
+                Some('}') => {
+                    self.bump();
+                    return Ok(());
+                }
+                Some('"') | Some('\'') | Some('`') => (self.string()?, true),
+                Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+                    (self.identifier().to_string(), false)
+                }
+                Some(_) => return Err(self.error("expected label name")),
+                None => return Err(self.error("unexpected end of input")),
+            };
+
+            self.skip_whitespace();
+
+            let label = match self.peek() {
+                // A quoted name that is not followed by an operator is a metric name.
+                Some(',') | Some('}') if quoted => Label::Equal((String::from("__name__"), name)),
+                _ => {
+                    let op = self.operator()?;
+                    self.skip_whitespace();
+                    let value = match self.peek() {
+                        Some('"') | Some('\'') | Some('`') => self.string()?,
+                        _ => return Err(self.error("expected label value")),
+                    };
+                    match op {
+                        "=" => Label::Equal((name, value)),
+                        "!=" => Label::NotEqual((name, value)),
+                        "=~" => Label::RegexEqual((name, value)),
+                        _ => Label::RegexNotEqual((name, value)),
+                    }
+                }
+            };
+
+            selector.labels.push(label);
+
+            self.skip_whitespace();
+
+            match self.peek() {
+                Some(',') => {
+                    self.bump();
+                }
+                Some('}') => {}
+                _ => return Err(self.error("expected ',' or '}'")),
+            }
+        }
+    }
+
+    fn operator(&mut self) -> Result<&'static str, Error> {
+        let rest = &self.input[self.pos..];
+        let op = if rest.starts_with("=~") {
+            "=~"
+        } else if rest.starts_with("!=") {
+            "!="
+        } else if rest.starts_with("!~") {
+            "!~"
+        } else if rest.starts_with('=') {
+            "="
+        } else {
+            return Err(self.error("expected label matching operator"));
+        };
+        self.pos += op.len();
+        Ok(op)
+    }
+
+    fn identifier(&mut self) -> &'a str {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':')
+        {
+            self.bump();
+        }
+        &self.input[start..self.pos]
+    }
+
+    // Parse a single-quoted, double-quoted or raw (backtick) string and decode escape
+    // sequences according to the PromQL string rules.
+    fn string(&mut self) -> Result<String, Error> {
+        let start = self.pos;
+        let quote = self.bump().ok_or_else(|| self.error("expected string"))?;
+        let mut bytes = vec![];
+
+        loop {
+            let c = self.bump().ok_or(Error::ParseSelector(ParseSelectorError {
+                message: "unterminated string",
+                position: start,
+            }))?;
+
+            if c == quote {
+                break;
+            }
+
+            if c != '\\' || quote == '`' {
+                bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                continue;
+            }
+
+            let escape_pos = self.pos - 1;
+            let invalid = ParseSelectorError {
+                message: "invalid escape sequence",
+                position: escape_pos,
+            };
+
+            let decoded = match self.bump() {
+                Some('a') => '\x07',
+                Some('b') => '\x08',
+                Some('f') => '\x0c',
+                Some('n') => '\n',
+                Some('r') => '\r',
+                Some('t') => '\t',
+                Some('v') => '\x0b',
+                Some(c @ ('\\' | '"' | '\'')) => c,
+                Some('x') => {
+                    bytes.push(self.digits(2, 16).ok_or(Error::ParseSelector(invalid))? as u8);
+                    continue;
+                }
+                Some('0'..='7') => {
+                    // Octal escapes consist of exactly three digits including the one just consumed.
+                    self.pos -= 1;
+                    let value = self
+                        .digits(3, 8)
+                        .filter(|v| *v <= 0xff)
+                        .ok_or(Error::ParseSelector(invalid))?;
+                    bytes.push(value as u8);
+                    continue;
+                }
+                Some(c @ ('u' | 'U')) => {
+                    let len = if c == 'u' { 4 } else { 8 };
+                    self.digits(len, 16)
+                        .and_then(char::from_u32)
+                        .ok_or(Error::ParseSelector(invalid))?
+                }
+                _ => return Err(Error::ParseSelector(invalid)),
+            };
+
+            bytes.extend_from_slice(decoded.encode_utf8(&mut [0; 4]).as_bytes());
+        }
+
+        String::from_utf8(bytes).map_err(|_| {
+            Error::ParseSelector(ParseSelectorError {
+                message: "string is not valid UTF-8",
+                position: start,
+            })
+        })
+    }
+
+    fn digits(&mut self, len: usize, radix: u32) -> Option<u32> {
+        let digits = self.input.get(self.pos..self.pos + len)?;
+        if !digits.chars().all(|c| c.is_digit(radix)) {
+            return None;
+        }
+        let value = u32::from_str_radix(digits, radix).ok()?;
+        self.pos += len;
+        Some(value)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn error(&self, message: &'static str) -> Error {
+        Error::ParseSelector(ParseSelectorError {
+            message,
+            position: self.pos,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -158,11 +517,11 @@ mod tests {
     fn test_selector_display_impl() {
         let s = Selector {
             labels: vec![
-                Label::Equal(("__name__", "http_requests_total")),
-                Label::Equal(("handler", "/api/comments")),
-                Label::RegexEqual(("job", ".*server")),
-                Label::RegexNotEqual(("status", "4..")),
-                Label::NotEqual(("env", "test")),
+                Label::Equal(("__name__".to_string(), "http_requests_total".to_string())),
+                Label::Equal(("handler".to_string(), "/api/comments".to_string())),
+                Label::RegexEqual(("job".to_string(), ".*server".to_string())),
+                Label::RegexNotEqual(("status".to_string(), "4..".to_string())),
+                Label::NotEqual(("env".to_string(), "test".to_string())),
             ],
         };
 
@@ -170,4 +529,100 @@ mod tests {
 
         assert_eq!(s.to_string(), result);
     }
+
+    #[test]
+    fn test_selector_escaping() {
+        let s = Selector::new()
+            .metric("http_requests_total")
+            .eq("path", "C:\\temp\n")
+            .regex_eq("handler", r#"/api/"v\d+""#)
+            .ne("service.name", "api");
+
+        let result = r#"{__name__="http_requests_total",path="C:\\temp\n",handler=~"/api/\"v\\d+\"","service.name"!="api"}"#;
+
+        assert_eq!(s.to_string(), result);
+    }
+
+    #[test]
+    fn test_selector_validation() -> Result<(), anyhow::Error> {
+        let s = Selector::new()
+            .try_metric("job:http_requests:rate5m")?
+            .try_eq("job", "api")?
+            .try_ne("_env", "test")?
+            .try_regex_eq("code", "5..")?
+            .try_regex_ne("handler1", "/health")?;
+
+        assert_eq!(s.labels.len(), 5);
+
+        for name in ["", "1abc", "service.name", "ÿ"] {
+            match Selector::new().try_eq(name, "x") {
+                Err(Error::InvalidSelector(e)) => assert_eq!(e.name(), name),
+                _ => panic!("label name {:?} should be rejected", name),
+            }
+        }
+
+        assert!(Selector::new().try_metric("").is_err());
+        assert!(Selector::new().try_metric("http-requests").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_selector_from_str() -> Result<(), anyhow::Error> {
+        let s: Selector =
+            r#"http_requests_total{job="api", env!="dev",code=~'5..' , path!~`/api/\d+`,}"#
+                .parse()?;
+        let expected = Selector::new()
+            .metric("http_requests_total")
+            .eq("job", "api")
+            .ne("env", "dev")
+            .regex_eq("code", "5..")
+            .regex_ne("path", r"/api/\d+");
+        assert_eq!(s, expected);
+
+        let s: Selector = r#"{"service.name"="a\"b\\c\n\x41\101\u00e4", "my.metric"}"#.parse()?;
+        let expected = Selector::new()
+            .eq("service.name", "a\"b\\c\nAAä")
+            .metric("my.metric");
+        assert_eq!(s, expected);
+
+        let s: Selector = " up ".parse()?;
+        assert_eq!(s, Selector::new().metric("up"));
+
+        let s: Selector = "{}".parse()?;
+        assert_eq!(s, Selector::new());
+
+        for (input, position) in [
+            ("", 0),
+            ("{job}", 4),
+            ("{job=api}", 5),
+            ("{job=\"api\"", 10),
+            ("{job=\"api", 5),
+            ("up{job=\"api\"} offset 5m", 14),
+            (r#"{job="\q"}"#, 6),
+        ] {
+            match input.parse::<Selector>() {
+                Err(Error::ParseSelector(e)) => assert_eq!(e.position(), position, "{}", input),
+                other => panic!("{:?} should fail to parse, got {:?}", input, other),
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_selector_roundtrip() -> Result<(), anyhow::Error> {
+        let s = Selector::new()
+            .metric("http_requests_total")
+            .eq("path", "C:\\temp\n\u{1}")
+            .regex_ne("service.name", "\"quoted\"");
+
+        assert_eq!(s.to_string().parse::<Selector>()?, s);
+
+        let json = serde_json::to_string(&s)?;
+        assert_eq!(json, serde_json::to_string(&s.to_string())?);
+        assert_eq!(serde_json::from_str::<Selector>(&json)?, s);
+
+        Ok(())
+    }
 }
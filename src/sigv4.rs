@@ -0,0 +1,299 @@
+use crate::error::{AuthError, Error};
+use hmac::{Hmac, Mac};
+use reqwest::header::{HeaderName, HeaderValue, AUTHORIZATION};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use time::OffsetDateTime;
+
+/// Configuration of AWS Signature Version 4 request signing, e.g. in order to query
+/// an Amazon Managed Service for Prometheus workspace. See [`Auth::SigV4`](crate::Auth::SigV4).
+///
+/// Requires the `aws-sigv4` feature.
+///
+/// ```rust
+/// use prometheus_http_query::{Auth, Client, SigV4};
+///
+/// fn main() -> Result<(), anyhow::Error> {
+///     let sigv4 = SigV4::new("eu-central-1", "AKIDEXAMPLE", "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY");
+///
+///     let client = Client::builder()
+///         .url("https://aps-workspaces.eu-central-1.amazonaws.com/workspaces/ws-example")
+///         .auth(Auth::SigV4(sigv4))
+///         .build()?;
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone)]
+pub struct SigV4 {
+    region: String,
+    service: String,
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+}
+
+impl fmt::Debug for SigV4 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SigV4")
+            .field("region", &self.region)
+            .field("service", &self.service)
+            .field("access_key_id", &self.access_key_id)
+            .field("secret_access_key", &"<redacted>")
+            .field(
+                "session_token",
+                &self.session_token.as_ref().map(|_| "<redacted>"),
+            )
+            .finish()
+    }
+}
+
+impl SigV4 {
+    /// Create a new configuration from static credentials. The service name defaults to `aps`
+    /// (Amazon Managed Service for Prometheus).
+    pub fn new(
+        region: impl std::fmt::Display,
+        access_key_id: impl std::fmt::Display,
+        secret_access_key: impl std::fmt::Display,
+    ) -> Self {
+        SigV4 {
+            region: region.to_string(),
+            service: String::from("aps"),
+            access_key_id: access_key_id.to_string(),
+            secret_access_key: secret_access_key.to_string(),
+            session_token: None,
+        }
+    }
+
+    /// Create a new configuration from the credentials in the environment variables `AWS_ACCESS_KEY_ID`,
+    /// `AWS_SECRET_ACCESS_KEY` and (optionally) `AWS_SESSION_TOKEN`. The service name defaults to `aps`
+    /// (Amazon Managed Service for Prometheus).
+    pub fn from_env(region: impl std::fmt::Display) -> Result<Self, Error> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+
+        let (access_key_id, secret_access_key) =
+            match (var("AWS_ACCESS_KEY_ID"), var("AWS_SECRET_ACCESS_KEY")) {
+                (Some(id), Some(secret)) => (id, secret),
+                _ => {
+                    return Err(Error::Auth(AuthError {
+                        message: "AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY must be set",
+                        source: None,
+                    }))
+                }
+            };
+
+        let mut sigv4 = SigV4::new(region, access_key_id, secret_access_key);
+        sigv4.session_token = var("AWS_SESSION_TOKEN");
+        Ok(sigv4)
+    }
+
+    /// Set the name of the service to sign requests for.
+    /// Calling this repeatedly will replace the current service name.
+    pub fn service(mut self, service: impl std::fmt::Display) -> Self {
+        self.service = service.to_string();
+        self
+    }
+
+    /// Set a session token as obtained for temporary credentials.
+    /// Calling this repeatedly will replace the current session token.
+    pub fn session_token(mut self, token: impl std::fmt::Display) -> Self {
+        self.session_token = Some(token.to_string());
+        self
+    }
+
+    // Sign the final request by adding the `X-Amz-Date`, `X-Amz-Security-Token` (if any)
+    // and `Authorization` headers.
+    pub(crate) fn sign(&self, request: &mut reqwest::Request, now: OffsetDateTime) {
+        let now = now.to_offset(time::UtcOffset::UTC);
+        let date = format!(
+            "{:04}{:02}{:02}",
+            now.year(),
+            u8::from(now.month()),
+            now.day()
+        );
+        let amz_date = format!(
+            "{}T{:02}{:02}{:02}Z",
+            date,
+            now.hour(),
+            now.minute(),
+            now.second()
+        );
+
+        let headers = request.headers_mut();
+        headers.insert(
+            HeaderName::from_static("x-amz-date"),
+            HeaderValue::from_str(&amz_date).unwrap(),
+        );
+        if let Some(token) = &self.session_token {
+            if let Ok(value) = HeaderValue::from_str(token) {
+                headers.insert(HeaderName::from_static("x-amz-security-token"), value);
+            }
+        }
+
+        let url = request.url();
+        let host = match url.port() {
+            Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+            None => url.host_str().unwrap_or_default().to_string(),
+        };
+
+        let mut signed: Vec<(String, String)> = vec![(String::from("host"), host)];
+        for name in ["x-amz-date", "x-amz-security-token"] {
+            if let Some(value) = request.headers().get(name) {
+                let value = String::from_utf8_lossy(value.as_bytes()).trim().to_string();
+                signed.push((name.to_string(), value));
+            }
+        }
+        signed.sort();
+
+        let canonical_headers: String = signed
+            .iter()
+            .map(|(k, v)| format!("{}:{}\n", k, v))
+            .collect();
+        let signed_headers = signed
+            .iter()
+            .map(|(k, _)| k.as_str())
+            .collect::<Vec<&str>>()
+            .join(";");
+
+        let body = request
+            .body()
+            .and_then(|b| b.as_bytes())
+            .unwrap_or_default();
+
+        let canonical_request = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            request.method().as_str(),
+            canonical_uri(request.url()),
+            canonical_query(request.url()),
+            canonical_headers,
+            signed_headers,
+            to_hex(&Sha256::digest(body))
+        );
+
+        let scope = format!("{}/{}/{}/aws4_request", date, self.region, self.service);
+
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            to_hex(&Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let key = format!("AWS4{}", self.secret_access_key);
+        let key = hmac(key.as_bytes(), date.as_bytes());
+        let key = hmac(&key, self.region.as_bytes());
+        let key = hmac(&key, self.service.as_bytes());
+        let key = hmac(&key, b"aws4_request");
+        let signature = to_hex(&hmac(&key, string_to_sign.as_bytes()));
+
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.access_key_id, scope, signed_headers, signature
+        );
+
+        if let Ok(value) = HeaderValue::from_str(&authorization) {
+            request.headers_mut().insert(AUTHORIZATION, value);
+        }
+    }
+}
+
+// Each path segment is encoded once more (on top of the encoding of the URL itself)
+// as required for all services except S3.
+fn canonical_uri(url: &url::Url) -> String {
+    let path = url.path();
+    if path.is_empty() {
+        return String::from("/");
+    }
+    path.split('/')
+        .map(uri_encode)
+        .collect::<Vec<String>>()
+        .join("/")
+}
+
+fn canonical_query(url: &url::Url) -> String {
+    let mut pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(k, v)| (uri_encode(&k), uri_encode(&v)))
+        .collect();
+    pairs.sort();
+    pairs
+        .iter()
+        .map(|(k, v)| format!("{}={}", k, v))
+        .collect::<Vec<String>>()
+        .join("&")
+}
+
+fn uri_encode(s: &str) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(b as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    encoded
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    // Test vectors from the AWS Signature Version 4 test suite.
+    #[test]
+    fn test_sigv4_signature() -> Result<(), anyhow::Error> {
+        let sigv4 = SigV4::new(
+            "us-east-1",
+            "AKIDEXAMPLE",
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+        )
+        .service("service");
+
+        let client = reqwest::Client::new();
+        let now = datetime!(2015-08-30 12:36:00 UTC);
+
+        let mut request = client.get("https://example.amazonaws.com/").build()?;
+        sigv4.sign(&mut request, now);
+        assert_eq!(request.headers()["x-amz-date"], "20150830T123600Z");
+        assert_eq!(
+            request.headers()[AUTHORIZATION],
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, SignedHeaders=host;x-amz-date, Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+        );
+
+        let mut request = client
+            .get("https://example.amazonaws.com/?Param2=value2&Param1=value1")
+            .build()?;
+        sigv4.sign(&mut request, now);
+        assert_eq!(
+            request.headers()[AUTHORIZATION],
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, SignedHeaders=host;x-amz-date, Signature=b97d918cfa904a5beff61c982a1b6f458b799221646efd99d3219ec94cdf2500"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sigv4_canonical_request_parts() -> Result<(), anyhow::Error> {
+        let url = url::Url::parse(
+            "https://aps-workspaces.us-east-1.amazonaws.com/workspaces/ws-1/api/v1/label/service.name%20x/values?match[]=up&end=2",
+        )?;
+        assert_eq!(
+            canonical_uri(&url),
+            "/workspaces/ws-1/api/v1/label/service.name%2520x/values"
+        );
+        assert_eq!(canonical_query(&url), "end=2&match%5B%5D=up");
+        Ok(())
+    }
+}
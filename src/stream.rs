@@ -0,0 +1,601 @@
+use crate::error::{ClientError, DeserializeError, Error};
+use crate::response::{Annotations, ApiResponse};
+use futures_util::stream::{self, BoxStream, Stream, StreamExt};
+use serde::de::{DeserializeOwned, IgnoredAny};
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+/// A stream of time series (or other elements of a response) that are parsed one at a time
+/// while the response body is received, see e.g. [`RangeQueryBuilder::get_stream`](crate::RangeQueryBuilder::get_stream).
+///
+/// In contrast to the regular methods that parse the entire response at once, only a single
+/// element is held in memory at any point in time, which keeps memory usage low for queries
+/// that return huge amounts of data.
+///
+/// Errors reported by Prometheus are returned as the first element of the stream, errors
+/// that occur while the response is received terminate the stream.
+///
+/// ```rust
+/// use prometheus_http_query::Client;
+/// use futures_util::TryStreamExt;
+///
+/// #[tokio::main(flavor = "current_thread")]
+/// async fn main() -> Result<(), anyhow::Error> {
+///     let client = Client::default();
+///
+///     let mut stream = client
+///         .query_range("up", 1648373100, 1648373300, 10.0)
+///         .get_stream()
+///         .await?;
+///
+///     while let Some(series) = stream.try_next().await? {
+///         println!("{:?}: {} samples", series.metric(), series.samples().len());
+///     }
+///
+///     if let Some(annotations) = stream.annotations() {
+///         assert!(annotations.warnings().is_empty());
+///     }
+///     Ok(())
+/// }
+/// ```
+pub struct SeriesStream<T> {
+    body: BoxStream<'static, Result<Vec<u8>, reqwest::Error>>,
+    parser: Parser,
+    finished: bool,
+    item: PhantomData<fn() -> T>,
+}
+
+impl<T> SeriesStream<T> {
+    pub(crate) fn new(response: reqwest::Response, target: Target) -> Self {
+        let body = stream::unfold(Some(response), |response| async move {
+            let mut response = response?;
+            match response.chunk().await {
+                Ok(Some(chunk)) => Some((Ok(chunk.to_vec()), Some(response))),
+                Ok(None) => None,
+                Err(e) => Some((Err(e), None)),
+            }
+        })
+        .boxed();
+
+        SeriesStream {
+            body,
+            parser: Parser::new(target),
+            finished: false,
+            item: PhantomData,
+        }
+    }
+
+    /// Return the warnings and infos that Prometheus attached to the response. As these
+    /// follow the data in the response body they are only available once the stream is exhausted.
+    pub fn annotations(&self) -> Option<&Annotations> {
+        self.parser.annotations.as_ref()
+    }
+}
+
+impl<T: DeserializeOwned> Stream for SeriesStream<T> {
+    type Item = Result<T, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        while !this.finished {
+            match this.parser.next() {
+                Ok(Next::Item(start, end)) => {
+                    let item = serde_json::from_slice(&this.parser.buf[start..end])
+                        .map_err(|source| invalid("failed to parse element of response", source));
+                    this.finished = item.is_err();
+                    return Poll::Ready(Some(item));
+                }
+                Ok(Next::End) => this.finished = true,
+                Ok(Next::NeedMore) => match ready!(this.body.poll_next_unpin(cx)) {
+                    Some(Ok(chunk)) => this.parser.push(&chunk),
+                    Some(Err(source)) => {
+                        this.finished = true;
+                        return Poll::Ready(Some(Err(Error::Client(ClientError {
+                            message: "failed to read response from server",
+                            source: Some(source.into()),
+                        }))));
+                    }
+                    None => {
+                        this.finished = true;
+                        return Poll::Ready(Some(Err(Error::Deserialize(DeserializeError {
+                            message: "unexpected end of response",
+                            source: None,
+                        }))));
+                    }
+                },
+                Err(e) => {
+                    this.finished = true;
+                    return Poll::Ready(Some(Err(e)));
+                }
+            }
+        }
+
+        Poll::Ready(None)
+    }
+}
+
+fn invalid(message: &'static str, source: serde_json::Error) -> Error {
+    Error::Deserialize(DeserializeError {
+        message,
+        source: Some(source),
+    })
+}
+
+fn syntax_error() -> Error {
+    Error::Deserialize(DeserializeError {
+        message: "invalid JSON in response",
+        source: None,
+    })
+}
+
+// The location of the array whose elements are streamed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Target {
+    // `data` is an array itself, e.g. for the series endpoint.
+    Data,
+    // `data.result` is an array of the given result type, i.e. for instant and range queries.
+    Result(&'static str),
+}
+
+#[derive(Debug, PartialEq)]
+enum State {
+    Start,
+    TopKey { first: bool },
+    TopValue { key: String },
+    DataKey { first: bool },
+    DataValue { key: String },
+    Items { first: bool },
+    Done,
+}
+
+enum Next {
+    Item(usize, usize),
+    NeedMore,
+    End,
+}
+
+// The outcome of a single parsing step.
+enum Step {
+    Continue,
+    NeedMore,
+    Item(usize, usize),
+}
+
+// Progress of scanning a single JSON value, so that scanning can be resumed where
+// it stopped once more data has been received.
+#[derive(Debug, Default)]
+struct Scan {
+    start: usize,
+    len: usize,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+}
+
+// An incremental parser for API responses. It walks the structure of the response object
+// down to the target array and returns the position of each element in the buffer once it
+// has been received completely. All other members of the response object are collected
+// and deserialized as `ApiResponse` when the end of the response has been reached.
+struct Parser {
+    buf: Vec<u8>,
+    pos: usize,
+    target: Target,
+    state: State,
+    scan: Option<Scan>,
+    members: Vec<(String, Vec<u8>)>,
+    annotations: Option<Annotations>,
+}
+
+impl Parser {
+    fn new(target: Target) -> Self {
+        Parser {
+            buf: Vec::new(),
+            pos: 0,
+            target,
+            state: State::Start,
+            scan: None,
+            members: vec![],
+            annotations: None,
+        }
+    }
+
+    fn push(&mut self, chunk: &[u8]) {
+        // Discard everything that has been processed already.
+        self.buf.drain(..self.pos);
+        if let Some(scan) = self.scan.as_mut() {
+            scan.start -= self.pos;
+        }
+        self.pos = 0;
+        self.buf.extend_from_slice(chunk);
+    }
+
+    fn next(&mut self) -> Result<Next, Error> {
+        loop {
+            let step = match std::mem::replace(&mut self.state, State::Done) {
+                State::Start => self.start(),
+                State::TopKey { first } => self.key(first, true),
+                State::TopValue { key } => self.top_value(key),
+                State::DataKey { first } => self.key(first, false),
+                State::DataValue { key } => self.data_value(key),
+                State::Items { first } => self.item(first),
+                State::Done => return self.finish().map(|_| Next::End),
+            };
+
+            match step? {
+                Step::Continue => {}
+                Step::NeedMore => return Ok(Next::NeedMore),
+                Step::Item(start, end) => return Ok(Next::Item(start, end)),
+            }
+        }
+    }
+
+    // Each of the following steps either consumes its input completely and sets the next state
+    // or restores the current state in order to be retried once more data has been received.
+
+    fn start(&mut self) -> Result<Step, Error> {
+        let Some(i) = self.skip_whitespace(self.pos) else {
+            self.state = State::Start;
+            return Ok(Step::NeedMore);
+        };
+        if self.buf[i] != b'{' {
+            return Err(syntax_error());
+        }
+        self.pos = i + 1;
+        self.state = State::TopKey { first: true };
+        Ok(Step::Continue)
+    }
+
+    // Parse the next member name of either the response object or the `data` object.
+    fn key(&mut self, first: bool, top: bool) -> Result<Step, Error> {
+        let retry = |p: &mut Parser| {
+            p.state = match top {
+                true => State::TopKey { first },
+                false => State::DataKey { first },
+            };
+            Ok(Step::NeedMore)
+        };
+
+        let Some(mut i) = self.skip_whitespace(self.pos) else {
+            return retry(self);
+        };
+
+        if self.buf[i] == b'}' {
+            self.pos = i + 1;
+            self.state = match top {
+                true => State::Done,
+                false => State::TopKey { first: false },
+            };
+            return Ok(Step::Continue);
+        }
+
+        if !first {
+            if self.buf[i] != b',' {
+                return Err(syntax_error());
+            }
+            match self.skip_whitespace(i + 1) {
+                Some(j) => i = j,
+                None => return retry(self),
+            }
+        }
+
+        let Some(end) = self.scan_value(i) else {
+            return retry(self);
+        };
+        let Some(colon) = self.skip_whitespace(end) else {
+            return retry(self);
+        };
+        if self.buf[colon] != b':' {
+            return Err(syntax_error());
+        }
+        let Some(value) = self.skip_whitespace(colon + 1) else {
+            return retry(self);
+        };
+
+        let key = serde_json::from_slice(&self.buf[i..end])
+            .map_err(|source| invalid("invalid member name in response", source))?;
+
+        self.pos = value;
+        self.state = match top {
+            true => State::TopValue { key },
+            false => State::DataValue { key },
+        };
+        Ok(Step::Continue)
+    }
+
+    fn top_value(&mut self, key: String) -> Result<Step, Error> {
+        if key == "data" {
+            match (self.target, self.buf[self.pos]) {
+                (Target::Data, b'[') => {
+                    self.pos += 1;
+                    self.state = State::Items { first: true };
+                    return Ok(Step::Continue);
+                }
+                (Target::Result(_), b'{') => {
+                    self.pos += 1;
+                    self.state = State::DataKey { first: true };
+                    return Ok(Step::Continue);
+                }
+                (_, b'n') => {}
+                _ => {
+                    return Err(Error::Deserialize(DeserializeError {
+                        message: "unexpected data in response",
+                        source: None,
+                    }))
+                }
+            }
+        }
+
+        let Some(end) = self.scan_value(self.pos) else {
+            self.state = State::TopValue { key };
+            return Ok(Step::NeedMore);
+        };
+        self.members.push((key, self.buf[self.pos..end].to_vec()));
+        self.pos = end;
+        self.state = State::TopKey { first: false };
+        Ok(Step::Continue)
+    }
+
+    fn data_value(&mut self, key: String) -> Result<Step, Error> {
+        if key == "result" && self.buf[self.pos] == b'[' {
+            self.pos += 1;
+            self.state = State::Items { first: true };
+            return Ok(Step::Continue);
+        }
+
+        let Some(end) = self.scan_value(self.pos) else {
+            self.state = State::DataValue { key };
+            return Ok(Step::NeedMore);
+        };
+
+        if key == "resultType" {
+            let result_type: String = serde_json::from_slice(&self.buf[self.pos..end])
+                .map_err(|source| invalid("invalid result type in response", source))?;
+            if matches!(self.target, Target::Result(expected) if expected != result_type) {
+                return Err(Error::Deserialize(DeserializeError {
+                    message: "unexpected result type in response",
+                    source: None,
+                }));
+            }
+        }
+
+        self.pos = end;
+        self.state = State::DataKey { first: false };
+        Ok(Step::Continue)
+    }
+
+    fn item(&mut self, first: bool) -> Result<Step, Error> {
+        self.state = State::Items { first };
+
+        let Some(mut i) = self.skip_whitespace(self.pos) else {
+            return Ok(Step::NeedMore);
+        };
+
+        if self.buf[i] == b']' {
+            self.pos = i + 1;
+            self.state = match self.target {
+                Target::Data => State::TopKey { first: false },
+                Target::Result(_) => State::DataKey { first: false },
+            };
+            return Ok(Step::Continue);
+        }
+
+        if !first {
+            if self.buf[i] != b',' {
+                return Err(syntax_error());
+            }
+            match self.skip_whitespace(i + 1) {
+                Some(j) => i = j,
+                None => return Ok(Step::NeedMore),
+            }
+        }
+
+        let Some(end) = self.scan_value(i) else {
+            return Ok(Step::NeedMore);
+        };
+        self.pos = end;
+        self.state = State::Items { first: false };
+        Ok(Step::Item(i, end))
+    }
+
+    fn finish(&mut self) -> Result<(), Error> {
+        // Reassemble the response object without the elements that have been
+        // returned already.
+        let mut object = b"{".to_vec();
+        if !self.members.iter().any(|(key, _)| key == "data") {
+            object.extend_from_slice(b"\"data\":null,");
+        }
+        for (i, (key, value)) in self.members.iter().enumerate() {
+            if i > 0 {
+                object.push(b',');
+            }
+            object.extend_from_slice(&serde_json::to_vec(key).unwrap_or_default());
+            object.push(b':');
+            object.extend_from_slice(value);
+        }
+        object.push(b'}');
+
+        let response = serde_json::from_slice::<ApiResponse<IgnoredAny>>(&object)
+            .map_err(|source| invalid("failed to parse JSON response from server", source))?;
+
+        match response {
+            ApiResponse::Success {
+                warnings, infos, ..
+            } => {
+                self.annotations = Some(Annotations { warnings, infos });
+                Ok(())
+            }
+            ApiResponse::Error(e) => Err(Error::Prometheus(e)),
+        }
+    }
+
+    fn skip_whitespace(&self, from: usize) -> Option<usize> {
+        (from..self.buf.len()).find(|i| !self.buf[*i].is_ascii_whitespace())
+    }
+
+    // Return the end of the JSON value that begins at `start` once it has been received
+    // completely. Values are not validated here but when they are deserialized.
+    fn scan_value(&mut self, start: usize) -> Option<usize> {
+        let mut scan = match self.scan.take() {
+            Some(scan) if scan.start == start => scan,
+            _ => Scan {
+                start,
+                ..Default::default()
+            },
+        };
+
+        while start + scan.len < self.buf.len() {
+            let b = self.buf[start + scan.len];
+
+            if scan.in_string {
+                if scan.escaped {
+                    scan.escaped = false;
+                } else if b == b'\\' {
+                    scan.escaped = true;
+                } else if b == b'"' {
+                    scan.in_string = false;
+                    if scan.depth == 0 {
+                        return Some(start + scan.len + 1);
+                    }
+                }
+                scan.len += 1;
+                continue;
+            }
+
+            match b {
+                b'"' => scan.in_string = true,
+                b'{' | b'[' => scan.depth += 1,
+                // Numbers and literals end at the next delimiter.
+                b'}' | b']' | b',' if scan.depth == 0 => return Some(start + scan.len),
+                b if b.is_ascii_whitespace() && scan.depth == 0 => return Some(start + scan.len),
+                b'}' | b']' => {
+                    scan.depth -= 1;
+                    if scan.depth == 0 {
+                        return Some(start + scan.len + 1);
+                    }
+                }
+                _ => {}
+            }
+            scan.len += 1;
+        }
+
+        self.scan = Some(scan);
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::response::{PromqlResult, RangeVector};
+    use std::collections::HashMap;
+
+    // Feed the data to the parser in chunks of the given size and collect all elements.
+    fn parse<T: DeserializeOwned>(
+        data: &str,
+        target: Target,
+        chunk_size: usize,
+    ) -> Result<(Vec<T>, Annotations), Error> {
+        let mut parser = Parser::new(target);
+        let mut chunks = data.as_bytes().chunks(chunk_size);
+        let mut items = vec![];
+
+        loop {
+            match parser.next()? {
+                Next::Item(start, end) => {
+                    items.push(serde_json::from_slice(&parser.buf[start..end]).unwrap())
+                }
+                Next::NeedMore => match chunks.next() {
+                    Some(chunk) => parser.push(chunk),
+                    None => return Err(syntax_error()),
+                },
+                Next::End => return Ok((items, parser.annotations.unwrap())),
+            }
+        }
+    }
+
+    #[test]
+    fn test_stream_range_vectors() -> Result<(), anyhow::Error> {
+        let data = r#"
+{
+  "status" : "success",
+  "data" : {
+    "resultType" : "matrix",
+    "result" : [
+      {
+        "metric" : { "__name__" : "up", "job" : "prometheus", "instance" : "localhost:9090" },
+        "values" : [ [ 1435781430.781, "1" ], [ 1435781445.781, "1" ] ]
+      },
+      {
+        "metric" : { "__name__" : "up", "job" : "node", "instance" : "local\"host:9091]}" },
+        "values" : [ [ 1435781430.781, "0" ], [ 1435781445.781, "1e-3" ] ]
+      }
+    ]
+  },
+  "warnings": ["PromQL warning: something is off"],
+  "infos": []
+}
+"#;
+        let expected = serde_json::from_str::<ApiResponse<PromqlResult>>(data)?;
+        let expected = match expected {
+            ApiResponse::Success { data, .. } => data.into_inner().0.into_matrix().unwrap(),
+            ApiResponse::Error(_) => unreachable!(),
+        };
+
+        for chunk_size in 1..=data.len() {
+            let (series, annotations) =
+                parse::<RangeVector>(data, Target::Result("matrix"), chunk_size)?;
+            assert_eq!(series, expected);
+            assert_eq!(annotations.warnings(), ["PromQL warning: something is off"]);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_stream_series() -> Result<(), anyhow::Error> {
+        let data = r#"{"status":"success","data":[{"__name__":"up","job":"a"},{"__name__":"up","job":"b\\"}]}"#;
+
+        for chunk_size in [1, 2, 7, data.len()] {
+            let (series, _) = parse::<HashMap<String, String>>(data, Target::Data, chunk_size)?;
+            assert_eq!(series.len(), 2);
+            assert_eq!(series[1]["job"], "b\\");
+        }
+
+        let (values, _) = parse::<String>(
+            r#"{"status":"success","data":["a","b","c"]}"#,
+            Target::Data,
+            3,
+        )?;
+        assert_eq!(values, ["a", "b", "c"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_stream_errors() {
+        let data =
+            r#"{"status":"error","errorType":"bad_data","error":"invalid parameter \"query\""}"#;
+        let result = parse::<RangeVector>(data, Target::Result("matrix"), 4);
+        assert!(matches!(result, Err(Error::Prometheus(_))));
+
+        let data = r#"{"status":"success","data":{"resultType":"scalar","result":[1,"1"]}}"#;
+        let result = parse::<RangeVector>(data, Target::Result("matrix"), 4);
+        assert!(matches!(result, Err(Error::Deserialize(_))));
+
+        let data = r#"{"status":"success","data":["up"]}"#;
+        let result = parse::<RangeVector>(data, Target::Result("matrix"), 4);
+        assert!(matches!(result, Err(Error::Deserialize(_))));
+
+        let data = r#"{"status":"success","data":{"resultType":"matrix","result":[]"#;
+        let result = parse::<RangeVector>(data, Target::Result("matrix"), 4);
+        assert!(matches!(result, Err(Error::Deserialize(_))));
+
+        let data = r#"{"status":"success","data":{"resultType":"matrix","result":[]}}"#;
+        let (series, annotations) =
+            parse::<RangeVector>(data, Target::Result("matrix"), 4).unwrap();
+        assert!(series.is_empty());
+        assert_eq!(annotations, Annotations::default());
+    }
+}
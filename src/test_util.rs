@@ -0,0 +1,68 @@
+// Fixtures shared by the tests of several modules.
+use crate::transport::{HttpRequest, HttpResponse, Transport, TransportFuture};
+use reqwest::header::{HeaderValue, CONTENT_TYPE};
+use reqwest::StatusCode;
+use std::error::Error as StdError;
+
+type TransportResult = Result<HttpResponse, Box<dyn StdError + Send + Sync>>;
+
+// A transport that answers every request using the given closure.
+pub(crate) struct StaticTransport<F>(F);
+
+impl<F> StaticTransport<F>
+where
+    F: Fn(&HttpRequest) -> TransportResult + Send + Sync,
+{
+    pub(crate) fn new(respond: F) -> Self {
+        StaticTransport(respond)
+    }
+}
+
+impl<F> Transport for StaticTransport<F>
+where
+    F: Fn(&HttpRequest) -> TransportResult + Send + Sync,
+{
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        let response = (self.0)(&request);
+        Box::pin(async move { response })
+    }
+}
+
+// A successful response with the given JSON body.
+pub(crate) fn json_response(body: impl Into<Vec<u8>>) -> HttpResponse {
+    HttpResponse::new(StatusCode::OK, body)
+        .header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
+}
+
+// A raw HTTP response with the given status line and JSON body, see `serve`.
+pub(crate) fn raw_response(status: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )
+}
+
+// Serve the given raw responses to consecutive connections and return the received requests
+// in lowercase. An empty response closes the connection without responding.
+pub(crate) fn serve(responses: Vec<String>) -> (String, std::thread::JoinHandle<Vec<String>>) {
+    use std::io::{Read, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+
+    let handle = std::thread::spawn(move || {
+        let mut requests = vec![];
+        for response in responses {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = vec![0; 8192];
+            let n = stream.read(&mut buf).unwrap();
+            requests.push(String::from_utf8_lossy(&buf[..n]).to_lowercase());
+            stream.write_all(response.as_bytes()).unwrap();
+        }
+        requests
+    });
+
+    (url, handle)
+}
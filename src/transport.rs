@@ -0,0 +1,185 @@
+use reqwest::header::{HeaderMap, HeaderValue, IntoHeaderName};
+use reqwest::{Method, StatusCode};
+use std::error::Error as StdError;
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+use url::Url;
+
+/// The future returned by [`Transport::send`].
+pub type TransportFuture<'a> = Pin<
+    Box<dyn Future<Output = Result<HttpResponse, Box<dyn StdError + Send + Sync>>> + Send + 'a>,
+>;
+
+/// A pluggable HTTP transport that sends the requests of a [`Client`](crate::Client),
+/// see [`ClientBuilder::transport`](crate::ClientBuilder::transport).
+///
+/// By default requests are sent using the [`reqwest::Client`] of the [`Client`](crate::Client).
+/// A custom transport receives the final request including default headers, credentials and
+/// the request body, so it can be used to plug in another HTTP implementation or to answer
+/// requests from memory in tests.
+///
+/// Errors returned by the transport are reported as [`Error::Transport`](crate::Error::Transport).
+/// A [`RetryPolicy`](crate::RetryPolicy) retries them if the transport reports a connection error
+/// or a timeout as a [`std::io::Error`] (possibly wrapped in another error) of the kind
+/// [`ConnectionRefused`](std::io::ErrorKind::ConnectionRefused), [`ConnectionReset`](std::io::ErrorKind::ConnectionReset),
+/// [`ConnectionAborted`](std::io::ErrorKind::ConnectionAborted), [`NotConnected`](std::io::ErrorKind::NotConnected)
+/// or [`TimedOut`](std::io::ErrorKind::TimedOut) respectively.
+/// Note that OAuth2 access tokens are always fetched using the [`reqwest::Client`].
+///
+/// ```rust
+/// use prometheus_http_query::{Client, HttpRequest, HttpResponse, Transport, TransportFuture};
+/// use reqwest::{header::HeaderValue, StatusCode};
+///
+/// struct Static;
+///
+/// impl Transport for Static {
+///     fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+///         Box::pin(async move {
+///             assert_eq!(request.url().path(), "/api/v1/query");
+///             let body = r#"{"status":"success","data":{"resultType":"scalar","result":[1,"1"]}}"#;
+///             Ok(HttpResponse::new(StatusCode::OK, body)
+///                 .header("content-type", HeaderValue::from_static("application/json")))
+///         })
+///     }
+/// }
+///
+/// #[tokio::main(flavor = "current_thread")]
+/// async fn main() -> Result<(), anyhow::Error> {
+///     let client = Client::builder().transport(Static).build()?;
+///
+///     let response = client.query("1").get().await?;
+///
+///     assert_eq!(response.data().as_scalar().unwrap().value(), 1.0);
+///     Ok(())
+/// }
+/// ```
+pub trait Transport: Send + Sync {
+    /// Send the request and return the response.
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_>;
+}
+
+impl Transport for reqwest::Client {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let mut builder = self
+                .request(request.method, request.url)
+                .headers(request.headers);
+
+            if let Some(body) = request.body {
+                builder = builder.body(body);
+            }
+
+            if let Some(timeout) = request.timeout {
+                builder = builder.timeout(timeout);
+            }
+
+            let response = builder.send().await?;
+
+            Ok(HttpResponse {
+                status: response.status(),
+                headers: response.headers().clone(),
+                body: response.bytes().await?.to_vec(),
+            })
+        })
+    }
+}
+
+/// A request that is sent using a [`Transport`].
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    pub(crate) method: Method,
+    pub(crate) url: Url,
+    pub(crate) headers: HeaderMap<HeaderValue>,
+    pub(crate) body: Option<Vec<u8>>,
+    pub(crate) timeout: Option<Duration>,
+}
+
+impl HttpRequest {
+    /// Return the HTTP method.
+    pub fn method(&self) -> &Method {
+        &self.method
+    }
+
+    /// Return the URL including the query string.
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Return the request headers.
+    pub fn headers(&self) -> &HeaderMap<HeaderValue> {
+        &self.headers
+    }
+
+    /// Return the request body, if any.
+    pub fn body(&self) -> Option<&[u8]> {
+        self.body.as_deref()
+    }
+
+    /// Return the timeout that is configured for the [`Client`](crate::Client), if any.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    pub(crate) fn from_request(request: reqwest::Request) -> Self {
+        HttpRequest {
+            method: request.method().clone(),
+            url: request.url().clone(),
+            headers: request.headers().clone(),
+            body: request
+                .body()
+                .and_then(|b| b.as_bytes())
+                .map(|b| b.to_vec()),
+            timeout: request.timeout().copied(),
+        }
+    }
+}
+
+/// A response that is returned by a [`Transport`].
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub(crate) status: StatusCode,
+    pub(crate) headers: HeaderMap<HeaderValue>,
+    pub(crate) body: Vec<u8>,
+}
+
+impl HttpResponse {
+    /// Create a response from a status code and the response body.
+    pub fn new(status: StatusCode, body: impl Into<Vec<u8>>) -> Self {
+        HttpResponse {
+            status,
+            headers: HeaderMap::new(),
+            body: body.into(),
+        }
+    }
+
+    /// Include an additional header in the response.
+    pub fn header<K: IntoHeaderName, T: Into<HeaderValue>>(mut self, name: K, value: T) -> Self {
+        self.headers.append(name, value.into());
+        self
+    }
+
+    /// Return the status code.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// Return the response headers.
+    pub fn headers(&self) -> &HeaderMap<HeaderValue> {
+        &self.headers
+    }
+
+    /// Return the response body.
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    // Convert into a `reqwest::Response` so that responses of custom transports are
+    // processed like any other response.
+    pub(crate) fn into_response(self) -> reqwest::Response {
+        let mut response = http::Response::new(self.body);
+        *response.status_mut() = self.status;
+        *response.headers_mut() = self.headers;
+        response.into()
+    }
+}
@@ -136,7 +136,7 @@ pub enum RuleKind {
 }
 
 impl RuleKind {
-    pub(crate) fn to_query_param(&self) -> String {
+    pub(crate) fn to_query_param(self) -> String {
         match self {
             Self::Alerting => String::from("alert"),
             Self::Recording => String::from("record"),
@@ -154,23 +154,77 @@ impl fmt::Display for RuleKind {
 }
 
 #[allow(clippy::enum_variant_names)]
-#[derive(Debug, Clone, PartialEq)]
-pub(crate) enum Label<'a> {
-    Equal((&'a str, &'a str)),
-    NotEqual((&'a str, &'a str)),
-    RegexEqual((&'a str, &'a str)),
-    RegexNotEqual((&'a str, &'a str)),
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum Label {
+    Equal((String, String)),
+    NotEqual((String, String)),
+    RegexEqual((String, String)),
+    RegexNotEqual((String, String)),
 }
 
-impl<'a> fmt::Display for Label<'a> {
+impl fmt::Display for Label {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
-        match self {
-            Self::Equal((k, v)) => write!(f, "{}=\"{}\"", k, v),
-            Self::NotEqual((k, v)) => write!(f, "{}!=\"{}\"", k, v),
-            Self::RegexEqual((k, v)) => write!(f, "{}=~\"{}\"", k, v),
-            Self::RegexNotEqual((k, v)) => write!(f, "{}!~\"{}\"", k, v),
+        let (k, op, v) = match self {
+            Self::Equal((k, v)) => (k, "=", v),
+            Self::NotEqual((k, v)) => (k, "!=", v),
+            Self::RegexEqual((k, v)) => (k, "=~", v),
+            Self::RegexNotEqual((k, v)) => (k, "!~", v),
+        };
+        write_label_name(f, k)?;
+        f.write_str(op)?;
+        write_quoted(f, v)
+    }
+}
+
+/// Check if the given string is a valid label name according to the legacy
+/// (pre-UTF-8) naming rules, i.e. it matches `[a-zA-Z_][a-zA-Z0-9_]*`.
+pub(crate) fn is_legacy_label_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        _ => false,
+    }
+}
+
+/// Check if the given string is a valid metric name according to the legacy
+/// (pre-UTF-8) naming rules, i.e. it matches `[a-zA-Z_:][a-zA-Z0-9_:]*`.
+pub(crate) fn is_legacy_metric_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' || c == ':' => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':')
+        }
+        _ => false,
+    }
+}
+
+/// Write a label name, quoting it as supported by Prometheus 3.x if it is not
+/// a valid legacy label name (e.g. `{"service.name"="api"}`).
+pub(crate) fn write_label_name(f: &mut fmt::Formatter<'_>, name: &str) -> fmt::Result {
+    if is_legacy_label_name(name) {
+        f.write_str(name)
+    } else {
+        write_quoted(f, name)
+    }
+}
+
+/// Write a double-quoted PromQL string literal, escaping characters as necessary.
+pub(crate) fn write_quoted(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    f.write_str("\"")?;
+    for c in s.chars() {
+        match c {
+            '\\' => f.write_str("\\\\")?,
+            '"' => f.write_str("\\\"")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
         }
     }
+    f.write_str("\"")
 }
 
 /// Create a base URL that is common to all queries from a string literal.
@@ -220,10 +274,10 @@ pub(crate) fn is_json(v: Option<&HeaderValue>) -> bool {
         .and_then(|h| h.to_str().ok())
         .and_then(|h| h.parse::<Mime>().ok())
     {
-        Some(mime) => match (mime.type_(), mime.subtype()) {
-            (mime::APPLICATION, mime::JSON) => true,
-            _ => false,
-        },
+        Some(mime) => matches!(
+            (mime.type_(), mime.subtype()),
+            (mime::APPLICATION, mime::JSON)
+        ),
         None => false,
     }
 }